@@ -1,27 +1,26 @@
-use std::{fmt, io, time::SystemTime};
+use std::{fmt, io, path::Path};
 
-use chrono::{DateTime, Utc};
 use serialport::SerialPort;
 use tracing::{error, instrument, trace, warn};
 
-use crate::system::simconnect_objects::GpsData;
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
 
-use super::{BroadcasterExt, ComConfig};
-
-const NMEA_MID_GSA_MESSAGE: &str =
-    "$GPGSA,A,3,01,02,03,04,05,06,07,08,09,10,11,12,1.0,1.0,1.0*30\r\n";
-const NMEA_MID_GSA_INTERVAL_S: u64 = 1;
+use super::{BroadcasterExt, ComConfig, ComProtocol, Nmea, Pty, Ubx};
 
 pub struct Com {
     port: Box<dyn SerialPort>,
-    last_mid_gsa: Option<SystemTime>,
+    pty: Option<Pty>,
+    protocol: ComProtocol,
+    nmea: Nmea,
 }
 
 impl fmt::Debug for Com {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Com")
             .field("port", &"...")
-            .field("last_mid_gsa", &self.last_mid_gsa)
+            .field("pty", &self.pty)
+            .field("protocol", &self.protocol)
+            .field("nmea", &self.nmea)
             .finish()
     }
 }
@@ -29,16 +28,29 @@ impl fmt::Debug for Com {
 impl Com {
     #[instrument(name = "Com::new")]
     pub fn new(config: ComConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
-        let port = serialport::new(config.port, config.baud_rate)
-            .open()
-            .map_err(|e| {
-                error!(error = ?e, "failed to open COM port");
+        let (port, pty) = if config.pty {
+            let (port, pty) = Pty::open(Path::new(&config.port)).map_err(|e| {
+                error!(error = ?e, "failed to open the pseudo-terminal");
                 e
             })?;
 
+            (port, Some(pty))
+        } else {
+            let port = serialport::new(config.port, config.baud_rate)
+                .open()
+                .map_err(|e| {
+                    error!(error = ?e, "failed to open COM port");
+                    e
+                })?;
+
+            (port, None)
+        };
+
         Ok(Box::new(Com {
             port,
-            last_mid_gsa: None,
+            pty,
+            protocol: config.protocol,
+            nmea: Nmea::new(config.nmea),
         }))
     }
 }
@@ -46,49 +58,51 @@ impl Com {
 impl BroadcasterExt for Com {
     #[instrument(name = "Com::send", skip(self, data))]
     fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
-        if self.should_send_mid_gsa() {
-            self.write(NMEA_MID_GSA_MESSAGE)?;
-            self.last_mid_gsa.replace(SystemTime::now());
-        }
-
-        let date = Utc::now();
-
-        let message = Self::convert_gps_data_to_nmea_mid_gga(&date, &data);
-        self.write(&message)?;
+        let messages = match self.protocol {
+            ComProtocol::Nmea => self
+                .nmea
+                .messages(&data)
+                .into_iter()
+                .map(String::into_bytes)
+                .collect(),
+            ComProtocol::Ubx => Ubx::messages(&data),
+        };
 
-        let message = Self::convert_gps_data_to_nmea_mid_rmc(&date, &data);
-        self.write(&message)?;
+        for message in messages {
+            self.write(&message)?;
+        }
 
         trace!("Successfully sent broadcast over COM");
 
         Ok(())
     }
-}
 
-impl Com {
-    fn should_send_mid_gsa(&self) -> bool {
-        match self.last_mid_gsa {
-            Some(last_mid_gsa) => {
-                if let Ok(elapsed) = last_mid_gsa.elapsed() {
-                    if elapsed.as_secs() >= NMEA_MID_GSA_INTERVAL_S {
-                        return true;
-                    }
-                }
-            }
-            None => {
-                return true;
-            }
+    #[instrument(name = "Com::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        if self.protocol != ComProtocol::Nmea {
+            return Ok(());
+        }
+
+        for message in self.nmea.attitude_messages(&data) {
+            self.write(message.as_bytes())?;
         }
 
-        false
+        Ok(())
     }
+}
 
-    fn write(&mut self, data: &str) -> Result<(), io::Error> {
-        match self.port.write(data.as_bytes()) {
+impl Com {
+    fn write(&mut self, data: &[u8]) -> Result<(), io::Error> {
+        match self.port.write_all(data) {
             Ok(_) => (),
-            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
-                warn!(error = ?e, "failed to write to COM (timeout)")
-            }
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => match self.pty.as_mut() {
+                // no app is reading from the pseudo-terminal
+                Some(pty) => pty.discard_unread().map_err(|e| {
+                    error!(error = ?e, "failed to discard the unread pseudo-terminal data");
+                    e
+                })?,
+                None => warn!(error = ?e, "failed to write to COM (timeout)"),
+            },
             Err(e) => {
                 error!(error = ?e, "failed to write to COM");
                 return Err(e);
@@ -97,68 +111,35 @@ impl Com {
 
         Ok(())
     }
-
-    fn convert_gps_data_to_nmea_mid_gga(date: &DateTime<Utc>, data: &GpsData) -> String {
-        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
-        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
-
-        let message = format!(
-            "$GPGGA,{},{:0>2}{:0>7.4},{},{:0>3}{:0>7.4},{},1,12,1.0,{:.1},M,0.0,M,,",
-            date.format("%H%M%S%.3f"),
-            lat_deg,
-            lat_min,
-            lat_dir,
-            lon_deg,
-            lon_min,
-            lon_dir,
-            data.pressure_altitude
-        );
-
-        let checksum = message.chars().skip(1).fold(0u8, |acc, c| acc ^ c as u8);
-
-        format!("{message}*{checksum:X}\r\n")
-    }
-
-    fn convert_gps_data_to_nmea_mid_rmc(date: &DateTime<Utc>, data: &GpsData) -> String {
-        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
-        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
-        let (magnetic_variation, magnetic_variation_dir) = data.gps_magnetic_variation_as_abs_dir();
-
-        let message = format!(
-            "$GPRMC,{},A,{:0>2}{:0>7.4},{},{:0>3}{:0>7.4},{},{:.2},{:.2},{},{:.1},{},S",
-            date.format("%H%M%S%.3f"),
-            lat_deg,
-            lat_min,
-            lat_dir,
-            lon_deg,
-            lon_min,
-            lon_dir,
-            data.gps_ground_speed_in_knots(),
-            data.gps_ground_true_track,
-            date.format("%d%m%y"),
-            magnetic_variation,
-            magnetic_variation_dir,
-        );
-
-        let checksum = message.chars().skip(1).fold(0u8, |acc, c| acc ^ c as u8);
-
-        format!("{message}*{checksum:X}\r\n")
-    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, unix))]
 mod tests {
-    use chrono::{NaiveDate, Utc};
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader},
+    };
+
+    use crate::broadcaster::{ComConfig, ComProtocol, NmeaConfig, NmeaProfile, NmeaSentence};
 
     use super::Com;
 
     #[test]
-    fn test_convert_gps_data_to_nmea_mid_gga() {
-        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
-            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
-            .unwrap()
-            .and_local_timezone(Utc)
-            .unwrap();
+    fn test_send_over_pty() {
+        let link = std::env::temp_dir().join(format!("msfs-gps-{}", std::process::id()));
+
+        let config = ComConfig {
+            port: link.to_string_lossy().to_string(),
+            baud_rate: 4800,
+            pty: true,
+            protocol: ComProtocol::Nmea,
+            nmea: NmeaConfig {
+                sentences: vec![NmeaSentence::Gga, NmeaSentence::Rmc],
+                profile: NmeaProfile::Standard,
+            },
+        };
+
+        let mut com = Com::new(config).unwrap();
 
         let data = crate::system::simconnect_objects::GpsData {
             lat: 51.509865,
@@ -168,49 +149,35 @@ mod tests {
             gps_ground_true_track: 310.55,
             gps_magnetic_variation: 5.0,
             gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
         };
 
-        let result = Com::convert_gps_data_to_nmea_mid_gga(&date, &data);
+        com.send(data).unwrap();
 
-        assert_eq!(
-            result,
-            "$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,3.0,M,0.0,M,,*70\r\n"
-        );
+        let mut lines = BufReader::new(File::open(&link).unwrap()).lines();
+        assert!(lines.next().unwrap().unwrap().starts_with("$GPGGA,"));
+        assert!(lines.next().unwrap().unwrap().starts_with("$GPRMC,"));
+
+        drop(com);
+        assert!(link.symlink_metadata().is_err());
     }
 
     #[test]
-    fn test_convert_gps_data_to_nmea_mid_gga_2() {
-        let date = NaiveDate::from_ymd_opt(2022, 1, 3)
-            .and_then(|d| d.and_hms_milli_opt(2, 1, 3, 75))
-            .unwrap()
-            .and_local_timezone(Utc)
-            .unwrap();
-
-        let data = crate::system::simconnect_objects::GpsData {
-            lat: 0.00040752447554520855,
-            lon: 0.01397450300629543,
-            alt: 0.0,
-            pressure_altitude: 0.9642891859251844,
-            gps_ground_true_track: 92.71680515837362,
-            gps_magnetic_variation: -4.384223296150313,
-            gps_ground_speed: 0.0,
+    fn test_send_over_pty_without_reader() {
+        let link = std::env::temp_dir().join(format!("msfs-gps-unread-{}", std::process::id()));
+
+        let config = ComConfig {
+            port: link.to_string_lossy().to_string(),
+            baud_rate: 4800,
+            pty: true,
+            protocol: ComProtocol::Nmea,
+            nmea: NmeaConfig {
+                sentences: vec![NmeaSentence::Gga, NmeaSentence::Rmc],
+                profile: NmeaProfile::Standard,
+            },
         };
 
-        let result = Com::convert_gps_data_to_nmea_mid_gga(&date, &data);
-
-        assert_eq!(
-            result,
-            "$GPGGA,020103.075,0000.0245,N,00000.8385,E,1,12,1.0,1.0,M,0.0,M,,*68\r\n"
-        );
-    }
-
-    #[test]
-    fn test_convert_gps_data_to_nmea_mid_rmc() {
-        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
-            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
-            .unwrap()
-            .and_local_timezone(Utc)
-            .unwrap();
+        let mut com = Com::new(config).unwrap();
 
         let data = crate::system::simconnect_objects::GpsData {
             lat: 51.509865,
@@ -220,39 +187,12 @@ mod tests {
             gps_ground_true_track: 310.55,
             gps_magnetic_variation: 5.0,
             gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
         };
 
-        let result = Com::convert_gps_data_to_nmea_mid_rmc(&date, &data);
-
-        assert_eq!(
-            result,
-            "$GPRMC,211030.750,A,5130.5919,N,00007.0855,W,195.36,310.55,301022,5.0,E,S*7\r\n"
-        );
-    }
-
-    #[test]
-    fn test_convert_gps_data_to_nmea_mid_rmc_2() {
-        let date = NaiveDate::from_ymd_opt(2022, 1, 3)
-            .and_then(|d| d.and_hms_milli_opt(2, 1, 3, 75))
-            .unwrap()
-            .and_local_timezone(Utc)
-            .unwrap();
-
-        let data = crate::system::simconnect_objects::GpsData {
-            lat: 0.00040752447554520855,
-            lon: 0.01397450300629543,
-            alt: 0.0,
-            pressure_altitude: 0.9642891859251844,
-            gps_ground_true_track: 92.71680515837362,
-            gps_magnetic_variation: -4.384223296150313,
-            gps_ground_speed: 0.0,
-        };
-
-        let result = Com::convert_gps_data_to_nmea_mid_rmc(&date, &data);
-
-        assert_eq!(
-            result,
-            "$GPRMC,020103.075,A,0000.0245,N,00000.8385,E,0.00,92.72,030122,4.4,W,S*3E\r\n"
-        );
+        // enough sentences to fill the pseudo-terminal buffer several times over
+        for _ in 0..500 {
+            com.send(data.clone()).unwrap();
+        }
     }
 }
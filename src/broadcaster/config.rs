@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -6,13 +8,93 @@ use serde::Deserialize;
 pub enum BroadcasterConfig {
     Udp(UdpConfig),
     Com(ComConfig),
+    Gdl90(Gdl90Config),
+    TcpServer(TcpServerConfig),
+    Gpsd(GpsdConfig),
+    WebSocket(WebSocketConfig),
+    Mqtt(MqttConfig),
+    Cot(CotConfig),
+    XPlaneData(XPlaneDataConfig),
+    Kml(KmlConfig),
+}
+
+impl BroadcasterConfig {
+    /// The `type` tag of the config.
+    pub fn broadcaster_type(&self) -> &'static str {
+        match self {
+            BroadcasterConfig::Udp(_) => "udp",
+            BroadcasterConfig::Com(_) => "com",
+            BroadcasterConfig::Gdl90(_) => "gdl90",
+            BroadcasterConfig::TcpServer(_) => "tcpServer",
+            BroadcasterConfig::Gpsd(_) => "gpsd",
+            BroadcasterConfig::WebSocket(_) => "webSocket",
+            BroadcasterConfig::Mqtt(_) => "mqtt",
+            BroadcasterConfig::Cot(_) => "cot",
+            BroadcasterConfig::XPlaneData(_) => "xPlaneData",
+            BroadcasterConfig::Kml(_) => "kml",
+        }
+    }
+
+    /// The rate at which the broadcaster sends the attitude, if it has its own.
+    pub fn attitude_rate_hz(&self) -> Option<u32> {
+        match self {
+            BroadcasterConfig::Udp(config) => config.attitude_rate_hz.filter(|rate| *rate > 0),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UdpConfig {
     pub port: u16,
-    pub netmask: String,
+    #[serde(default)]
+    pub netmask: Option<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub multicast: Option<MulticastConfig>,
+    #[serde(default)]
+    pub attitude_rate_hz: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gdl90Config {
+    #[serde(default = "default_gdl90_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub netmask: Option<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub multicast: Option<MulticastConfig>,
+    // the 24-bit ICAO address reported in the ownship report
+    #[serde(default = "default_gdl90_ownship_address")]
+    pub ownship_address: u32,
+}
+
+impl Gdl90Config {
+    pub fn udp(&self) -> UdpConfig {
+        UdpConfig {
+            port: self.port,
+            netmask: self.netmask.clone(),
+            targets: self.targets.clone(),
+            multicast: self.multicast.clone(),
+            attitude_rate_hz: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MulticastConfig {
+    pub group: IpAddr,
+    #[serde(default)]
+    pub ttl: Option<u32>,
+    // an IPv4 address for IPv4 groups and an interface index for IPv6 groups
+    #[serde(default)]
+    pub interface: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -20,4 +102,243 @@ pub struct UdpConfig {
 pub struct ComConfig {
     pub port: String,
     pub baud_rate: u32,
+    // expose a pseudo-terminal through a symlink at `port` instead of opening a serial port
+    #[serde(default)]
+    pub pty: bool,
+    #[serde(default)]
+    pub protocol: ComProtocol,
+    #[serde(flatten)]
+    pub nmea: NmeaConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ComProtocol {
+    #[default]
+    Nmea,
+    // u-blox binary NAV-PVT, NAV-POSLLH and NAV-VELNED messages
+    Ubx,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpServerConfig {
+    pub bind: String,
+    pub port: u16,
+    #[serde(flatten)]
+    pub nmea: NmeaConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsdConfig {
+    #[serde(default = "default_gpsd_bind")]
+    pub bind: String,
+    #[serde(default = "default_gpsd_port")]
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketConfig {
+    #[serde(default = "default_websocket_bind")]
+    pub bind: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    #[serde(default = "default_mqtt_position_topic")]
+    pub position_topic: String,
+    #[serde(default = "default_mqtt_track_topic")]
+    pub track_topic: String,
+    #[serde(default = "default_mqtt_speed_topic")]
+    pub speed_topic: String,
+    #[serde(default = "default_mqtt_status_topic")]
+    pub status_topic: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CotConfig {
+    #[serde(default)]
+    pub protocol: CotProtocol,
+    #[serde(default = "default_cot_address")]
+    pub address: String,
+    #[serde(default = "default_cot_uid")]
+    pub uid: String,
+    #[serde(default = "default_cot_callsign")]
+    pub callsign: String,
+    #[serde(default = "default_cot_event_type")]
+    pub event_type: String,
+    #[serde(default = "default_cot_stale_seconds")]
+    pub stale_seconds: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CotProtocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XPlaneDataConfig {
+    #[serde(flatten)]
+    pub udp: UdpConfig,
+    #[serde(default = "default_xplane_packets")]
+    pub packets: Vec<XPlanePacket>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum XPlanePacket {
+    Data,
+    Rpos,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KmlConfig {
+    #[serde(default = "default_kml_bind")]
+    pub bind: String,
+    pub port: u16,
+    // the number of positions kept for the track line
+    #[serde(default = "default_kml_track_points")]
+    pub track_points: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaConfig {
+    #[serde(default = "default_nmea_sentences")]
+    pub sentences: Vec<NmeaSentence>,
+    #[serde(default)]
+    pub profile: NmeaProfile,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NmeaProfile {
+    #[default]
+    Standard,
+    Garmin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NmeaSentence {
+    Gga,
+    Rmc,
+    Gsa,
+    Gsv,
+    Vtg,
+    Gll,
+    Zda,
+    Hdt,
+    Hdg,
+}
+
+fn default_nmea_sentences() -> Vec<NmeaSentence> {
+    vec![
+        NmeaSentence::Gga,
+        NmeaSentence::Rmc,
+        NmeaSentence::Gsa,
+        NmeaSentence::Gsv,
+    ]
+}
+
+fn default_xplane_packets() -> Vec<XPlanePacket> {
+    vec![XPlanePacket::Data]
+}
+
+fn default_gdl90_port() -> u16 {
+    4000
+}
+
+// a self-assigned address in the block that ICAO keeps for special use,
+// because 0x000000 is not a valid address
+fn default_gdl90_ownship_address() -> u32 {
+    0xF00001
+}
+
+fn default_gpsd_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_gpsd_port() -> u16 {
+    2947
+}
+
+fn default_websocket_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "msfs-2020-gps-link".to_string()
+}
+
+fn default_mqtt_position_topic() -> String {
+    "msfs/position".to_string()
+}
+
+fn default_mqtt_track_topic() -> String {
+    "msfs/track".to_string()
+}
+
+fn default_mqtt_speed_topic() -> String {
+    "msfs/speed".to_string()
+}
+
+fn default_mqtt_status_topic() -> String {
+    "msfs/status".to_string()
+}
+
+fn default_cot_address() -> String {
+    "239.2.3.1:6969".to_string()
+}
+
+fn default_cot_uid() -> String {
+    "msfs-2020-gps-link".to_string()
+}
+
+fn default_cot_callsign() -> String {
+    "MSFS".to_string()
+}
+
+// atom, friendly, air, civilian, fixed wing
+fn default_cot_event_type() -> String {
+    "a-f-A-C-F".to_string()
+}
+
+fn default_cot_stale_seconds() -> u32 {
+    30
+}
+
+fn default_kml_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_kml_track_points() -> usize {
+    3600
 }
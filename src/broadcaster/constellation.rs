@@ -0,0 +1,291 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::system::simconnect_objects::GpsData;
+
+// a simplified, evenly spaced Walker 24/6/1 model of the GPS constellation with circular orbits
+const ORBITAL_PLANES: u8 = 6;
+const SATELLITES_PER_PLANE: u8 = 4;
+const ORBIT_INCLINATION_DEG: f64 = 55.0;
+const ORBIT_SEMI_MAJOR_AXIS_M: f64 = 26_559_700.0;
+// half a sidereal day
+const ORBIT_PERIOD_S: f64 = 43_082.05;
+const EARTH_ROTATION_RATE_RAD_S: f64 = 7.292_115_146_7e-5;
+// the right ascension of the Greenwich meridian at the J2000 epoch
+const GREENWICH_SIDEREAL_ANGLE_AT_EPOCH_DEG: f64 = 280.460_618_37;
+
+const WGS84_SEMI_MAJOR_AXIS_M: f64 = 6_378_137.0;
+const WGS84_ECCENTRICITY_SQUARED: f64 = 6.694_379_990_14e-3;
+
+const ELEVATION_MASK_DEG: f64 = 5.0;
+const MAX_TRACKED_SATELLITES: usize = 12;
+const MIN_SATELLITES_FOR_3D_FIX: usize = 4;
+// the value receivers report when the dilution of precision cannot be computed
+const UNKNOWN_DILUTION_OF_PRECISION: f64 = 99.9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Satellite {
+    pub prn: u8,
+    pub elevation: f64,
+    pub azimuth: f64,
+    pub snr: u8,
+}
+
+/// The satellites a receiver at the aircraft position would be tracking, together with the resulting dilution of precision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constellation {
+    pub satellites: Vec<Satellite>,
+    pub pdop: f64,
+    pub hdop: f64,
+    pub vdop: f64,
+}
+
+impl Constellation {
+    pub fn visible_from(date: &DateTime<Utc>, data: &GpsData) -> Self {
+        let receiver = geodetic_to_ecef(data.lat, data.lon, data.alt);
+        let seconds_since_epoch = (*date - j2000_epoch()).num_milliseconds() as f64 / 1000.0;
+
+        let mut satellites = (0..ORBITAL_PLANES)
+            .flat_map(|plane| (0..SATELLITES_PER_PLANE).map(move |slot| (plane, slot)))
+            .filter_map(|(plane, slot)| {
+                let position = satellite_ecef(plane, slot, seconds_since_epoch);
+                let (elevation, azimuth) = look_angles(data.lat, data.lon, receiver, position);
+
+                (elevation >= ELEVATION_MASK_DEG).then(|| Satellite {
+                    prn: plane * SATELLITES_PER_PLANE + slot + 1,
+                    elevation,
+                    azimuth,
+                    snr: (30.0 + 20.0 * elevation.to_radians().sin()).round() as u8,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        satellites.sort_by(|a, b| b.elevation.total_cmp(&a.elevation));
+        satellites.truncate(MAX_TRACKED_SATELLITES);
+
+        let (pdop, hdop, vdop) = dilution_of_precision(&satellites).unwrap_or((
+            UNKNOWN_DILUTION_OF_PRECISION,
+            UNKNOWN_DILUTION_OF_PRECISION,
+            UNKNOWN_DILUTION_OF_PRECISION,
+        ));
+
+        Self {
+            satellites,
+            pdop,
+            hdop,
+            vdop,
+        }
+    }
+}
+
+fn j2000_epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0)
+        .single()
+        .expect("the J2000 epoch is a valid date")
+}
+
+fn geodetic_to_ecef(lat: f64, lon: f64, alt: f64) -> [f64; 3] {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    let prime_vertical_radius =
+        WGS84_SEMI_MAJOR_AXIS_M / (1.0 - WGS84_ECCENTRICITY_SQUARED * lat.sin().powi(2)).sqrt();
+
+    [
+        (prime_vertical_radius + alt) * lat.cos() * lon.cos(),
+        (prime_vertical_radius + alt) * lat.cos() * lon.sin(),
+        (prime_vertical_radius * (1.0 - WGS84_ECCENTRICITY_SQUARED) + alt) * lat.sin(),
+    ]
+}
+
+fn satellite_ecef(plane: u8, slot: u8, seconds_since_epoch: f64) -> [f64; 3] {
+    let mean_motion = 2.0 * PI / ORBIT_PERIOD_S;
+    let inclination = ORBIT_INCLINATION_DEG.to_radians();
+
+    // the planes are 60 degrees apart and the satellites within a plane are 90 degrees apart,
+    // with a 15 degrees phase offset between adjacent planes
+    let right_ascension = (f64::from(plane) * 360.0 / f64::from(ORBITAL_PLANES)).to_radians();
+    let argument_of_latitude = (f64::from(slot) * 360.0 / f64::from(SATELLITES_PER_PLANE)
+        + f64::from(plane) * 15.0)
+        .to_radians()
+        + mean_motion * seconds_since_epoch;
+
+    // the longitude of the ascending node in the Earth-fixed frame
+    let node_longitude = right_ascension
+        - GREENWICH_SIDEREAL_ANGLE_AT_EPOCH_DEG.to_radians()
+        - EARTH_ROTATION_RATE_RAD_S * seconds_since_epoch;
+
+    let (x, y) = (
+        ORBIT_SEMI_MAJOR_AXIS_M * argument_of_latitude.cos(),
+        ORBIT_SEMI_MAJOR_AXIS_M * argument_of_latitude.sin(),
+    );
+
+    [
+        x * node_longitude.cos() - y * inclination.cos() * node_longitude.sin(),
+        x * node_longitude.sin() + y * inclination.cos() * node_longitude.cos(),
+        y * inclination.sin(),
+    ]
+}
+
+/// Returns the elevation and the true azimuth, in degrees, of `target` as seen from `receiver`.
+fn look_angles(lat: f64, lon: f64, receiver: [f64; 3], target: [f64; 3]) -> (f64, f64) {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    let [dx, dy, dz] = [
+        target[0] - receiver[0],
+        target[1] - receiver[1],
+        target[2] - receiver[2],
+    ];
+
+    let east = -lon.sin() * dx + lon.cos() * dy;
+    let north = -lat.sin() * lon.cos() * dx - lat.sin() * lon.sin() * dy + lat.cos() * dz;
+    let up = lat.cos() * lon.cos() * dx + lat.cos() * lon.sin() * dy + lat.sin() * dz;
+
+    let elevation = up.atan2(east.hypot(north)).to_degrees();
+    let azimuth = east.atan2(north).to_degrees().rem_euclid(360.0);
+
+    (elevation, azimuth)
+}
+
+/// Computes the position, horizontal and vertical dilution of precision from the satellite geometry.
+fn dilution_of_precision(satellites: &[Satellite]) -> Option<(f64, f64, f64)> {
+    if satellites.len() < MIN_SATELLITES_FOR_3D_FIX {
+        return None;
+    }
+
+    // the normal matrix G^T * G, where each row of G is the unit line of sight in east-north-up plus the clock term
+    let mut normal = [[0.0; 4]; 4];
+
+    for satellite in satellites {
+        let (elevation, azimuth) = (
+            satellite.elevation.to_radians(),
+            satellite.azimuth.to_radians(),
+        );
+        let row = [
+            elevation.cos() * azimuth.sin(),
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            1.0,
+        ];
+
+        for (i, normal_row) in normal.iter_mut().enumerate() {
+            for (j, value) in normal_row.iter_mut().enumerate() {
+                *value += row[i] * row[j];
+            }
+        }
+    }
+
+    let covariance = invert(normal)?;
+
+    let pdop = (covariance[0][0] + covariance[1][1] + covariance[2][2]).sqrt();
+    let hdop = (covariance[0][0] + covariance[1][1]).sqrt();
+    let vdop = covariance[2][2].sqrt();
+
+    Some((pdop, hdop, vdop))
+}
+
+/// Gauss-Jordan elimination with partial pivoting.
+fn invert(mut matrix: [[f64; 4]; 4]) -> Option<[[f64; 4]; 4]> {
+    let mut inverse = [[0.0; 4]; 4];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for column in 0..4 {
+        let pivot = (column..4)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let divisor = matrix[column][column];
+        for j in 0..4 {
+            matrix[column][j] /= divisor;
+            inverse[column][j] /= divisor;
+        }
+
+        for row in 0..4 {
+            if row != column {
+                let factor = matrix[row][column];
+                for j in 0..4 {
+                    matrix[row][j] -= factor * matrix[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::Constellation;
+
+    #[test]
+    fn test_visible_from() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Constellation::visible_from(&date, &data);
+
+        assert!(result.satellites.len() >= 4 && result.satellites.len() <= 12);
+        assert!(result
+            .satellites
+            .windows(2)
+            .all(|pair| pair[0].elevation >= pair[1].elevation));
+        assert!(result.satellites.iter().all(|satellite| {
+            (5.0..=90.0).contains(&satellite.elevation)
+                && (0.0..360.0).contains(&satellite.azimuth)
+                && (1..=24).contains(&satellite.prn)
+                && (30..=50).contains(&satellite.snr)
+        }));
+        assert!(result.hdop > 0.5 && result.hdop < 5.0);
+        assert!(result.vdop > 0.5 && result.vdop < 10.0);
+        assert!((result.pdop.powi(2) - result.hdop.powi(2) - result.vdop.powi(2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_visible_from_changes_over_time() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 0.00040752447554520855,
+            lon: 0.01397450300629543,
+            alt: 0.0,
+            pressure_altitude: 0.9642891859251844,
+            gps_ground_true_track: 92.71680515837362,
+            gps_magnetic_variation: -4.384223296150313,
+            gps_ground_speed: 0.0,
+            vertical_speed: 0.0,
+        };
+
+        let date = NaiveDate::from_ymd_opt(2022, 1, 3)
+            .and_then(|d| d.and_hms_milli_opt(2, 1, 3, 75))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+        let later = date + chrono::Duration::hours(2);
+
+        let result = Constellation::visible_from(&date, &data);
+        let result_later = Constellation::visible_from(&later, &data);
+
+        assert_ne!(result.satellites, result_later.satellites);
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::escape::escape;
+use tracing::{error, info, instrument, trace, warn};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{BroadcasterExt, CotConfig, CotProtocol, MulticastConfig, UdpConfig, UdpTransport};
+
+const COT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const COT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const COT_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+// machine generated, GPS derived
+const COT_HOW: &str = "m-g";
+const COT_CIRCULAR_ERROR_M: f64 = GpsData::POSITION_ACCURACY_M;
+// the simulator does not model the geoid, so the height above the ellipsoid and its error are unknown
+const COT_UNKNOWN: f64 = 9_999_999.0;
+
+/// Cursor-on-Target events for ATAK and WinTAK, sent over UDP (usually the SA multicast group) or to a TCP endpoint.
+#[derive(Debug)]
+pub struct Cot {
+    transport: CotTransport,
+    config: CotConfig,
+}
+
+#[derive(Debug)]
+enum CotTransport {
+    Udp(UdpTransport),
+    Tcp(CotTcpConnection),
+}
+
+/// The TCP endpoint, reconnected on the next event once the connection has dropped.
+#[derive(Debug)]
+struct CotTcpConnection {
+    address: SocketAddr,
+    stream: Option<TcpStream>,
+    // set when connecting fails, so that an unreachable endpoint does not block every event
+    last_failure: Option<Instant>,
+}
+
+impl Cot {
+    #[instrument(name = "Cot::new")]
+    pub fn new(config: CotConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        let address = Self::resolve(&config.address)?;
+
+        let transport = match config.protocol {
+            CotProtocol::Udp => {
+                let (multicast, targets) = if address.ip().is_multicast() {
+                    let multicast = MulticastConfig {
+                        group: address.ip(),
+                        ttl: None,
+                        interface: None,
+                    };
+
+                    (Some(multicast), Vec::new())
+                } else {
+                    (None, vec![address.to_string()])
+                };
+
+                CotTransport::Udp(UdpTransport::new(UdpConfig {
+                    port: address.port(),
+                    netmask: None,
+                    targets,
+                    multicast,
+                    attitude_rate_hz: None,
+                })?)
+            }
+            CotProtocol::Tcp => CotTransport::Tcp(CotTcpConnection {
+                address,
+                stream: Some(CotTcpConnection::connect(&address)?),
+                last_failure: None,
+            }),
+        };
+
+        Ok(Box::new(Cot { transport, config }))
+    }
+
+    fn resolve(address: &str) -> Result<SocketAddr, io::Error> {
+        address
+            .to_socket_addrs()
+            .map_err(|e| {
+                error!(error = ?e, address = ?address, "failed to resolve the CoT address");
+                e
+            })?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "the CoT address did not resolve to any address",
+                )
+            })
+    }
+}
+
+impl BroadcasterExt for Cot {
+    #[instrument(name = "Cot::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        let event = Self::convert_gps_data_to_event(&self.config, &Utc::now(), &data);
+
+        match &mut self.transport {
+            CotTransport::Udp(transport) => transport.send(event.as_bytes())?,
+            CotTransport::Tcp(connection) => connection.send(event.as_bytes()),
+        }
+
+        trace!("Successfully sent CoT event");
+
+        Ok(())
+    }
+}
+
+impl Cot {
+    fn convert_gps_data_to_event(
+        config: &CotConfig,
+        date: &DateTime<Utc>,
+        data: &GpsData,
+    ) -> String {
+        let time = date.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let stale = (*date + chrono::Duration::seconds(config.stale_seconds.into()))
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+             <event version=\"2.0\" uid=\"{}\" type=\"{}\" how=\"{}\" time=\"{}\" start=\"{}\" stale=\"{}\">\
+             <point lat=\"{:.7}\" lon=\"{:.7}\" hae=\"{:.1}\" ce=\"{:.1}\" le=\"{:.1}\"/>\
+             <detail>\
+             <contact callsign=\"{}\"/>\
+             <track course=\"{:.1}\" speed=\"{:.1}\"/>\
+             <precisionlocation geopointsrc=\"GPS\" altsrc=\"???\"/>\
+             </detail>\
+             </event>",
+            escape(&config.uid),
+            escape(&config.event_type),
+            COT_HOW,
+            time,
+            time,
+            stale,
+            data.lat,
+            data.lon,
+            COT_UNKNOWN,
+            COT_CIRCULAR_ERROR_M,
+            COT_UNKNOWN,
+            escape(&config.callsign),
+            data.gps_ground_true_track,
+            data.gps_ground_speed,
+        )
+    }
+}
+
+impl CotTcpConnection {
+    fn connect(address: &SocketAddr) -> Result<TcpStream, io::Error> {
+        let stream = TcpStream::connect_timeout(address, COT_CONNECT_TIMEOUT).map_err(|e| {
+            error!(error = ?e, "failed to connect to the CoT TCP endpoint");
+            e
+        })?;
+        stream.set_write_timeout(Some(COT_WRITE_TIMEOUT))?;
+
+        Ok(stream)
+    }
+
+    /// Failures are only logged, as the connection is attempted again on the next event.
+    fn send(&mut self, event: &[u8]) {
+        if self.stream.is_none()
+            && self
+                .last_failure
+                .is_none_or(|last_failure| last_failure.elapsed() >= COT_RECONNECT_INTERVAL)
+        {
+            match Self::connect(&self.address) {
+                Ok(stream) => {
+                    info!(address = ?self.address, "reconnected to the CoT TCP endpoint");
+                    self.stream.replace(stream);
+                    self.last_failure = None;
+                }
+                Err(_) => {
+                    self.last_failure.replace(Instant::now());
+                }
+            }
+        }
+
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+
+        if let Err(e) = stream.write_all(event) {
+            warn!(error = ?e, "failed to send over the CoT TCP connection, reconnecting");
+            self.stream = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        net::TcpListener,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::broadcaster::{CotConfig, CotProtocol};
+
+    use super::Cot;
+
+    #[test]
+    fn test_convert_gps_data_to_event() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let config = CotConfig {
+            protocol: CotProtocol::Udp,
+            address: "239.2.3.1:6969".to_string(),
+            uid: "msfs-2020-gps-link".to_string(),
+            callsign: "SAR <1>".to_string(),
+            event_type: "a-f-A-C-F".to_string(),
+            stale_seconds: 30,
+        };
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Cot::convert_gps_data_to_event(&config, &date, &data);
+
+        assert_eq!(
+            result,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+             <event version=\"2.0\" uid=\"msfs-2020-gps-link\" type=\"a-f-A-C-F\" how=\"m-g\" time=\"2022-10-30T21:10:30.750Z\" start=\"2022-10-30T21:10:30.750Z\" stale=\"2022-10-30T21:11:00.750Z\">\
+             <point lat=\"51.5098650\" lon=\"-0.1180920\" hae=\"9999999.0\" ce=\"3.0\" le=\"9999999.0\"/>\
+             <detail><contact callsign=\"SAR &lt;1&gt;\"/><track course=\"310.6\" speed=\"100.5\"/><precisionlocation geopointsrc=\"GPS\" altsrc=\"???\"/></detail>\
+             </event>"
+        );
+    }
+
+    #[test]
+    fn test_send_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = CotConfig {
+            protocol: CotProtocol::Tcp,
+            address: listener.local_addr().unwrap().to_string(),
+            uid: "msfs-2020-gps-link".to_string(),
+            callsign: "MSFS".to_string(),
+            event_type: "a-f-A-C-F".to_string(),
+            stale_seconds: 30,
+        };
+
+        let mut cot = Cot::new(config).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        cot.send(data).unwrap();
+        drop(cot);
+
+        let mut result = String::new();
+        stream.read_to_string(&mut result).unwrap();
+
+        assert!(result.contains("<event version=\"2.0\" uid=\"msfs-2020-gps-link\""));
+        assert!(result.ends_with("</event>"));
+    }
+
+    #[test]
+    fn test_reconnect_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = CotConfig {
+            protocol: CotProtocol::Tcp,
+            address: listener.local_addr().unwrap().to_string(),
+            uid: "msfs-2020-gps-link".to_string(),
+            callsign: "MSFS".to_string(),
+            event_type: "a-f-A-C-F".to_string(),
+            stale_seconds: 30,
+        };
+
+        let mut cot = Cot::new(config).unwrap();
+        drop(listener.accept().unwrap());
+        listener.set_nonblocking(true).unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        // the dropped connection is only noticed by a failing write
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut stream = loop {
+            cot.send(data.clone()).unwrap();
+
+            if let Ok((stream, _)) = listener.accept() {
+                break stream;
+            }
+
+            assert!(
+                Instant::now() < deadline,
+                "the CoT TCP connection was not reopened"
+            );
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        stream.set_nonblocking(false).unwrap();
+        cot.send(data).unwrap();
+        drop(cot);
+
+        let mut result = String::new();
+        stream.read_to_string(&mut result).unwrap();
+
+        assert!(result.starts_with("<?xml"));
+        assert!(result.ends_with("</event>"));
+    }
+}
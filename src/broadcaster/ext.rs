@@ -1,7 +1,11 @@
 use std::{fmt, io};
 
-use crate::system::simconnect_objects::GpsData;
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
 
 pub trait BroadcasterExt: fmt::Debug {
     fn send(&mut self, data: GpsData) -> Result<(), io::Error>;
+
+    fn send_attitude(&mut self, _data: AttitudeData) -> Result<(), io::Error> {
+        Ok(())
+    }
 }
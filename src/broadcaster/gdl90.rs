@@ -0,0 +1,360 @@
+use std::{io, time::SystemTime};
+
+use chrono::{DateTime, Timelike, Utc};
+use tracing::{instrument, trace};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{BroadcasterExt, Gdl90Config, UdpTransport};
+
+const GDL90_FLAG_BYTE: u8 = 0x7E;
+const GDL90_CONTROL_ESCAPE_BYTE: u8 = 0x7D;
+const GDL90_ESCAPE_XOR: u8 = 0x20;
+
+const GDL90_MESSAGE_ID_HEARTBEAT: u8 = 0;
+const GDL90_MESSAGE_ID_OWNSHIP_REPORT: u8 = 10;
+const GDL90_MESSAGE_ID_OWNSHIP_GEOMETRIC_ALTITUDE: u8 = 11;
+
+const GDL90_HEARTBEAT_INTERVAL_S: u64 = 1;
+
+const GDL90_OWNSHIP_CALL_SIGN: &[u8; 8] = b"MSFS    ";
+// light aircraft (< 15500 lbs)
+const GDL90_OWNSHIP_EMITTER_CATEGORY: u8 = 1;
+// the simulator position is exact, so we report the best integrity categories
+// NIC 11: containment radius < 7.5 m, NACp 11: estimated position uncertainty < 3 m
+const GDL90_OWNSHIP_NIC: u8 = 11;
+const GDL90_OWNSHIP_NACP: u8 = 11;
+const GDL90_OWNSHIP_VERTICAL_FIGURE_OF_MERIT_M: u16 = 10;
+
+const GDL90_INVALID_ALTITUDE: u16 = 0xFFF;
+// the vertical velocity is a 12-bit signed value in units of 64 fpm, up to +/- 32576 fpm
+const GDL90_MAX_VERTICAL_VELOCITY: f64 = 509.0;
+
+const FEET_PER_METER: f64 = 3.28084;
+const FEET_PER_MINUTE_PER_METER_PER_SECOND: f64 = 196.850394;
+
+const CRC16_TABLE: [u16; 256] = crc16_table();
+
+/// GDL 90 over UDP, as described in the
+/// [GDL 90 Data Interface Specification](https://www.faa.gov/sites/faa.gov/files/air_traffic/technology/adsb/archival/GDL90_Public_ICD_RevA.PDF).
+#[derive(Debug)]
+pub struct Gdl90 {
+    transport: UdpTransport,
+    ownship_address: u32,
+    last_heartbeat: Option<SystemTime>,
+}
+
+impl Gdl90 {
+    #[instrument(name = "Gdl90::new")]
+    pub fn new(config: Gdl90Config) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        if config.ownship_address == 0 || config.ownship_address > 0xFFFFFF {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the ownship address must be a non-zero 24-bit ICAO address",
+            ));
+        }
+
+        let transport = UdpTransport::new(config.udp())?;
+
+        Ok(Box::new(Gdl90 {
+            transport,
+            ownship_address: config.ownship_address,
+            last_heartbeat: None,
+        }))
+    }
+}
+
+impl BroadcasterExt for Gdl90 {
+    #[instrument(name = "Gdl90::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        if self.should_send_heartbeat() {
+            let message = Self::heartbeat_message(&Utc::now());
+            self.transport.send(&Self::frame(&message))?;
+            self.last_heartbeat.replace(SystemTime::now());
+        }
+
+        let message = Self::ownship_report_message(&data, self.ownship_address);
+        self.transport.send(&Self::frame(&message))?;
+
+        let message = Self::ownship_geometric_altitude_message(&data);
+        self.transport.send(&Self::frame(&message))?;
+
+        trace!("Successfully sent GDL90 over UDP");
+
+        Ok(())
+    }
+}
+
+impl Gdl90 {
+    fn should_send_heartbeat(&self) -> bool {
+        match self.last_heartbeat {
+            Some(last_heartbeat) => {
+                if let Ok(elapsed) = last_heartbeat.elapsed() {
+                    if elapsed.as_secs() >= GDL90_HEARTBEAT_INTERVAL_S {
+                        return true;
+                    }
+                }
+            }
+            None => {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn heartbeat_message(date: &DateTime<Utc>) -> Vec<u8> {
+        let timestamp = date.num_seconds_from_midnight();
+
+        // GPS position valid, UAT initialized
+        let status_1 = 0b1000_0001;
+        // timestamp bit 16, UTC OK
+        let status_2 = (((timestamp >> 16) & 0x01) << 7) as u8 | 0b0000_0001;
+
+        vec![
+            GDL90_MESSAGE_ID_HEARTBEAT,
+            status_1,
+            status_2,
+            (timestamp & 0xFF) as u8,
+            ((timestamp >> 8) & 0xFF) as u8,
+            // no uplink or basic and long messages have been received
+            0x00,
+            0x00,
+        ]
+    }
+
+    fn ownship_report_message(data: &GpsData, address: u32) -> Vec<u8> {
+        let lat = Self::encode_semicircles(data.lat);
+        let lon = Self::encode_semicircles(data.lon);
+
+        let altitude = Self::encode_pressure_altitude(data.pressure_altitude);
+        // airborne, updated report, true track angle
+        let misc = 0b1001;
+
+        let horizontal_velocity =
+            data.gps_ground_speed_in_knots().round().clamp(0.0, 4094.0) as u16;
+        let vertical_velocity = Self::encode_vertical_velocity(data.vertical_speed);
+        let track =
+            (data.gps_ground_true_track.rem_euclid(360.0) * 256.0 / 360.0).round() as u16 as u8;
+
+        let mut message = Vec::with_capacity(28);
+
+        message.push(GDL90_MESSAGE_ID_OWNSHIP_REPORT);
+        // no traffic alert, ADS-B with ICAO address
+        message.push(0x00);
+        message.extend_from_slice(&address.to_be_bytes()[1..]);
+        message.extend_from_slice(&lat);
+        message.extend_from_slice(&lon);
+        message.push((altitude >> 4) as u8);
+        message.push((((altitude & 0x0F) as u8) << 4) | misc);
+        message.push((GDL90_OWNSHIP_NIC << 4) | GDL90_OWNSHIP_NACP);
+        message.push((horizontal_velocity >> 4) as u8);
+        message.push((((horizontal_velocity & 0x0F) as u8) << 4) | (vertical_velocity >> 8) as u8);
+        message.push((vertical_velocity & 0xFF) as u8);
+        message.push(track);
+        message.push(GDL90_OWNSHIP_EMITTER_CATEGORY);
+        message.extend_from_slice(GDL90_OWNSHIP_CALL_SIGN);
+        // no emergency
+        message.push(0x00);
+
+        message
+    }
+
+    fn ownship_geometric_altitude_message(data: &GpsData) -> Vec<u8> {
+        let altitude = (data.alt * FEET_PER_METER / 5.0)
+            .round()
+            .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+
+        let mut message = Vec::with_capacity(5);
+
+        message.push(GDL90_MESSAGE_ID_OWNSHIP_GEOMETRIC_ALTITUDE);
+        message.extend_from_slice(&altitude.to_be_bytes());
+        // no vertical warning
+        message.extend_from_slice(&GDL90_OWNSHIP_VERTICAL_FIGURE_OF_MERIT_M.to_be_bytes());
+
+        message
+    }
+
+    /// Encodes a latitude or longitude as a 24-bit signed binary fraction with a resolution of 180 / 2^23 degrees.
+    fn encode_semicircles(degrees: f64) -> [u8; 3] {
+        let value = (degrees * f64::from(1 << 23) / 180.0).round() as i32;
+        let bytes = value.to_be_bytes();
+
+        [bytes[1], bytes[2], bytes[3]]
+    }
+
+    /// Encodes a pressure altitude as a 12-bit value with a resolution of 25 feet and an offset of -1000 feet.
+    fn encode_pressure_altitude(pressure_altitude: f64) -> u16 {
+        let altitude = ((pressure_altitude * FEET_PER_METER + 1000.0) / 25.0).round();
+
+        if (0.0..GDL90_INVALID_ALTITUDE as f64).contains(&altitude) {
+            altitude as u16
+        } else {
+            GDL90_INVALID_ALTITUDE
+        }
+    }
+
+    /// Encodes a vertical speed as a 12-bit two's complement value with a resolution of 64 feet per minute.
+    fn encode_vertical_velocity(vertical_speed: f64) -> u16 {
+        let vertical_velocity = (vertical_speed * FEET_PER_MINUTE_PER_METER_PER_SECOND / 64.0)
+            .round()
+            .clamp(-GDL90_MAX_VERTICAL_VELOCITY, GDL90_MAX_VERTICAL_VELOCITY);
+
+        (vertical_velocity as i16 as u16) & 0xFFF
+    }
+
+    /// Appends the CRC, escapes the flag and control-escape bytes, and surrounds the result with flag bytes.
+    fn frame(message: &[u8]) -> Vec<u8> {
+        let crc = Self::crc16(message);
+
+        let mut frame = Vec::with_capacity(message.len() * 2 + 4);
+        frame.push(GDL90_FLAG_BYTE);
+
+        for &byte in message.iter().chain(crc.to_le_bytes().iter()) {
+            if byte == GDL90_FLAG_BYTE || byte == GDL90_CONTROL_ESCAPE_BYTE {
+                frame.push(GDL90_CONTROL_ESCAPE_BYTE);
+                frame.push(byte ^ GDL90_ESCAPE_XOR);
+            } else {
+                frame.push(byte);
+            }
+        }
+
+        frame.push(GDL90_FLAG_BYTE);
+
+        frame
+    }
+
+    fn crc16(message: &[u8]) -> u16 {
+        message.iter().fold(0u16, |crc, &byte| {
+            CRC16_TABLE[(crc >> 8) as usize] ^ (crc << 8) ^ byte as u16
+        })
+    }
+}
+
+/// CRC-CCITT lookup table, as used by the GDL 90 Frame Check Sequence.
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = (crc << 1) ^ if crc & 0x8000 != 0 { 0x1021 } else { 0 };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::Gdl90;
+
+    #[test]
+    fn test_crc16() {
+        let message = [0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02];
+
+        let result = Gdl90::crc16(&message);
+
+        assert_eq!(result, 0x8BB3);
+    }
+
+    #[test]
+    fn test_frame() {
+        let message = [0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02];
+
+        let result = Gdl90::frame(&message);
+
+        assert_eq!(
+            result,
+            vec![0x7E, 0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02, 0xB3, 0x8B, 0x7E]
+        );
+    }
+
+    #[test]
+    fn test_frame_byte_stuffing() {
+        let message = [0x7E, 0x7D, 0x01];
+
+        let result = Gdl90::frame(&message);
+
+        assert_eq!(
+            result,
+            vec![0x7E, 0x7D, 0x5E, 0x7D, 0x5D, 0x01, 0x58, 0xE2, 0x7E]
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_message() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let result = Gdl90::heartbeat_message(&date);
+
+        // 76230 seconds since midnight, bit 16 goes into the second status byte
+        assert_eq!(result, vec![0x00, 0x81, 0x81, 0xC6, 0x29, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_ownship_report_message() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 1000.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            // 1000 fpm
+            vertical_speed: 5.08,
+        };
+
+        let result = Gdl90::ownship_report_message(&data, 0xF00001);
+
+        assert_eq!(
+            result,
+            vec![
+                0x0A, 0x00, 0xF0, 0x00, 0x01, 0x24, 0xA1, 0x16, 0xFF, 0xEA, 0x81, 0x0A, 0xB9, 0xBB,
+                0x0C, 0x30, 0x10, 0xDD, 0x01, 0x4D, 0x53, 0x46, 0x53, 0x20, 0x20, 0x20, 0x20, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_vertical_velocity() {
+        assert_eq!(Gdl90::encode_vertical_velocity(0.0), 0x000);
+        // -1000 fpm
+        assert_eq!(Gdl90::encode_vertical_velocity(-5.08), 0xFF0);
+        assert_eq!(Gdl90::encode_vertical_velocity(500.0), 0x1FD);
+        assert_eq!(Gdl90::encode_vertical_velocity(-500.0), 0xE03);
+    }
+
+    #[test]
+    fn test_ownship_geometric_altitude_message() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 1000.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Gdl90::ownship_geometric_altitude_message(&data);
+
+        // 3281 ft in 5 ft increments
+        assert_eq!(result, vec![0x0B, 0x02, 0x90, 0x00, 0x0A]);
+    }
+}
@@ -0,0 +1,682 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument, trace, warn};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{BroadcasterExt, Constellation, GpsdConfig};
+
+const GPSD_PROTOCOL_MAJOR: u8 = 3;
+const GPSD_PROTOCOL_MINOR: u8 = 14;
+const GPSD_RELEASE: &str = "3.22";
+const GPSD_DEVICE_PATH: &str = "msfs";
+const GPSD_DEVICE_DRIVER: &str = "MSFS 2020 GPS Link";
+
+const GPSD_ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+const GPSD_SKY_INTERVAL_S: u64 = 1;
+// clients that send more than this without terminating a request are disconnected
+const GPSD_MAX_PENDING_REQUEST_LEN: usize = 4096;
+
+/// A [gpsd](https://gpsd.gitlab.io/gpsd/gpsd_json.html) compatible JSON server.
+#[derive(Debug)]
+pub struct Gpsd {
+    state: Arc<Mutex<GpsdState>>,
+    last_sky: Option<SystemTime>,
+    shutdown: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+struct GpsdState {
+    clients: Vec<GpsdClient>,
+    last_tpv: Option<Tpv>,
+    last_sky_report: Option<Sky>,
+}
+
+#[derive(Debug)]
+struct GpsdClient {
+    addr: SocketAddr,
+    stream: TcpStream,
+    pending: Vec<u8>,
+    watching: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Request {
+    Version,
+    Devices,
+    Watch(WatchRequest),
+    Poll,
+    Unknown(String),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct WatchRequest {
+    #[serde(default = "default_watch_enable")]
+    enable: bool,
+}
+
+fn default_watch_enable() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "class", rename_all = "UPPERCASE")]
+enum Report {
+    Version(Version),
+    Devices(Devices),
+    Watch(Watch),
+    Tpv(Tpv),
+    Sky(Sky),
+    Poll(Poll),
+    Error(Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Version {
+    release: &'static str,
+    rev: &'static str,
+    proto_major: u8,
+    proto_minor: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Devices {
+    devices: Vec<Device>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Device {
+    class: &'static str,
+    path: &'static str,
+    driver: &'static str,
+    // the device has seen GPS data
+    flags: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Watch {
+    enable: bool,
+    json: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Tpv {
+    device: &'static str,
+    mode: u8,
+    time: String,
+    lat: f64,
+    lon: f64,
+    #[serde(rename = "altMSL")]
+    alt_msl: f64,
+    // the pre 3.20 name of the altitude, still read by older clients
+    alt: f64,
+    track: f64,
+    magtrack: f64,
+    magvar: f64,
+    speed: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Sky {
+    device: &'static str,
+    time: String,
+    hdop: f64,
+    vdop: f64,
+    pdop: f64,
+    satellites: Vec<SkySatellite>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SkySatellite {
+    #[serde(rename = "PRN")]
+    prn: u8,
+    el: f64,
+    az: f64,
+    ss: f64,
+    used: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Poll {
+    time: String,
+    active: u8,
+    tpv: Vec<Tpv>,
+    sky: Vec<Sky>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Error {
+    message: String,
+}
+
+impl Gpsd {
+    #[instrument(name = "Gpsd::new")]
+    pub fn new(config: GpsdConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        Ok(Box::new(Self::bind(config)?.0))
+    }
+
+    fn bind(config: GpsdConfig) -> Result<(Self, SocketAddr), io::Error> {
+        let listener = TcpListener::bind((config.bind.as_str(), config.port)).map_err(|e| {
+            error!(error = ?e, "failed to bind the gpsd listener");
+            e
+        })?;
+        listener.set_nonblocking(true).map_err(|e| {
+            error!(error = ?e, "failed to set the gpsd listener to non-blocking");
+            e
+        })?;
+
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(GpsdState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        // clients are greeted and answered from their own thread, so the handshake does not wait for data
+        let server_state = state.clone();
+        let server_shutdown = shutdown.clone();
+        thread::Builder::new()
+            .name("gpsd".to_string())
+            .spawn(move || Self::serve(listener, server_state, server_shutdown))?;
+
+        info!(address = ?addr, "gpsd server listening");
+
+        Ok((
+            Gpsd {
+                state,
+                last_sky: None,
+                shutdown,
+            },
+            addr,
+        ))
+    }
+}
+
+impl BroadcasterExt for Gpsd {
+    #[instrument(name = "Gpsd::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        let date = Utc::now();
+
+        let tpv = Self::convert_gps_data_to_tpv(&date, &data);
+        let mut reports = vec![Report::Tpv(tpv.clone())];
+
+        let sky = if self.should_send_sky() {
+            let constellation = Constellation::visible_from(&date, &data);
+            let sky = Self::convert_constellation_to_sky(&date, &constellation);
+            reports.push(Report::Sky(sky.clone()));
+            self.last_sky.replace(SystemTime::now());
+            Some(sky)
+        } else {
+            None
+        };
+
+        let reports = Self::serialize(&reports);
+
+        let mut state = self.state.lock().expect("the gpsd state lock is poisoned");
+        state.last_tpv.replace(tpv);
+        if let Some(sky) = sky {
+            state.last_sky_report.replace(sky);
+        }
+
+        state.clients.retain_mut(|client| {
+            if !client.watching {
+                return true;
+            }
+
+            client.write(&reports)
+        });
+
+        trace!("Successfully sent gpsd reports");
+
+        Ok(())
+    }
+}
+
+impl Gpsd {
+    fn should_send_sky(&self) -> bool {
+        match self.last_sky {
+            Some(last_sky) => {
+                if let Ok(elapsed) = last_sky.elapsed() {
+                    if elapsed.as_secs() >= GPSD_SKY_INTERVAL_S {
+                        return true;
+                    }
+                }
+            }
+            None => {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn serve(listener: TcpListener, state: Arc<Mutex<GpsdState>>, shutdown: Arc<AtomicBool>) {
+        while !shutdown.load(Ordering::Relaxed) {
+            Self::accept_clients(&listener, &state);
+            Self::handle_requests(&state);
+
+            thread::sleep(GPSD_ACCEPT_INTERVAL);
+        }
+
+        trace!("gpsd server closed");
+    }
+
+    fn accept_clients(listener: &TcpListener, state: &Mutex<GpsdState>) {
+        loop {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    // the stream is never allowed to block the broadcaster;
+                    // like gpsd, clients that cannot keep up are disconnected
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        warn!(error = ?e, client = ?addr, "failed to set the gpsd client to non-blocking");
+                        continue;
+                    }
+
+                    let mut client = GpsdClient {
+                        addr,
+                        stream,
+                        pending: Vec::new(),
+                        watching: false,
+                    };
+
+                    info!(client = ?addr, "gpsd client connected");
+
+                    if client.write(&Self::serialize(&[Self::version()])) {
+                        state
+                            .lock()
+                            .expect("the gpsd state lock is poisoned")
+                            .clients
+                            .push(client);
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!(error = ?e, "failed to accept a gpsd client");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_requests(state: &Mutex<GpsdState>) {
+        let date = Utc::now();
+
+        let mut state = state.lock().expect("the gpsd state lock is poisoned");
+        let GpsdState {
+            clients,
+            last_tpv,
+            last_sky_report,
+        } = &mut *state;
+
+        clients.retain_mut(|client| {
+            let Some(requests) = client.read_requests() else {
+                return false;
+            };
+
+            let mut reports = Vec::new();
+
+            for request in requests {
+                match request {
+                    Request::Version => reports.push(Self::version()),
+                    Request::Devices => reports.push(Self::devices()),
+                    Request::Watch(watch) => {
+                        client.watching = watch.enable;
+
+                        if watch.enable {
+                            reports.push(Self::devices());
+                        }
+                        reports.push(Report::Watch(Watch {
+                            enable: watch.enable,
+                            json: watch.enable,
+                        }));
+                    }
+                    Request::Poll => reports.push(Report::Poll(Poll {
+                        time: Self::format_time(&date),
+                        active: 1,
+                        tpv: last_tpv.iter().cloned().collect(),
+                        sky: last_sky_report.iter().cloned().collect(),
+                    })),
+                    Request::Unknown(request) => reports.push(Report::Error(Error {
+                        message: format!("Unrecognized request '{request}'"),
+                    })),
+                }
+            }
+
+            reports.is_empty() || client.write(&Self::serialize(&reports))
+        });
+    }
+
+    /// Splits the complete requests off the start of `pending`, leaving any partial request in place.
+    fn parse_requests(pending: &mut Vec<u8>) -> Vec<Request> {
+        let Some(end) = pending.iter().rposition(|b| *b == b';' || *b == b'\n') else {
+            return Vec::new();
+        };
+
+        let complete = pending.drain(..=end).collect::<Vec<_>>();
+
+        String::from_utf8_lossy(&complete)
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|request| !request.is_empty())
+            .map(|request| {
+                let (command, arguments) = request.split_once('=').unwrap_or((request, ""));
+
+                match command {
+                    "?VERSION" => Request::Version,
+                    "?DEVICES" => Request::Devices,
+                    "?POLL" => Request::Poll,
+                    "?WATCH" if arguments.is_empty() => Request::Watch(WatchRequest {
+                        enable: default_watch_enable(),
+                    }),
+                    "?WATCH" => serde_json::from_str(arguments)
+                        .map(Request::Watch)
+                        .unwrap_or_else(|_| Request::Unknown(request.to_string())),
+                    _ => Request::Unknown(request.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    fn convert_gps_data_to_tpv(date: &DateTime<Utc>, data: &GpsData) -> Tpv {
+        Tpv {
+            device: GPSD_DEVICE_PATH,
+            // 3D fix
+            mode: 3,
+            time: Self::format_time(date),
+            lat: data.lat,
+            lon: data.lon,
+            alt_msl: data.alt,
+            alt: data.alt,
+            track: data.gps_ground_true_track,
+            magtrack: (data.gps_ground_true_track - data.gps_magnetic_variation).rem_euclid(360.0),
+            magvar: data.gps_magnetic_variation,
+            speed: data.gps_ground_speed,
+        }
+    }
+
+    fn convert_constellation_to_sky(date: &DateTime<Utc>, constellation: &Constellation) -> Sky {
+        Sky {
+            device: GPSD_DEVICE_PATH,
+            time: Self::format_time(date),
+            hdop: constellation.hdop,
+            vdop: constellation.vdop,
+            pdop: constellation.pdop,
+            satellites: constellation
+                .satellites
+                .iter()
+                .map(|satellite| SkySatellite {
+                    prn: satellite.prn,
+                    el: satellite.elevation.round(),
+                    az: satellite.azimuth.round(),
+                    ss: f64::from(satellite.snr),
+                    used: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn version() -> Report {
+        Report::Version(Version {
+            release: GPSD_RELEASE,
+            rev: GPSD_RELEASE,
+            proto_major: GPSD_PROTOCOL_MAJOR,
+            proto_minor: GPSD_PROTOCOL_MINOR,
+        })
+    }
+
+    fn devices() -> Report {
+        Report::Devices(Devices {
+            devices: vec![Device {
+                class: "DEVICE",
+                path: GPSD_DEVICE_PATH,
+                driver: GPSD_DEVICE_DRIVER,
+                flags: 1,
+            }],
+        })
+    }
+
+    fn format_time(date: &DateTime<Utc>) -> String {
+        date.to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    fn serialize(reports: &[Report]) -> String {
+        reports
+            .iter()
+            .filter_map(|report| {
+                serde_json::to_string(report)
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to serialize the gpsd report");
+                        e
+                    })
+                    .ok()
+            })
+            .map(|report| format!("{report}\r\n"))
+            .collect()
+    }
+}
+
+impl GpsdClient {
+    /// Returns `None` once the client has disconnected.
+    fn read_requests(&mut self) -> Option<Vec<Request>> {
+        let mut buffer = [0u8; 512];
+
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    info!(client = ?self.addr, "gpsd client disconnected");
+                    return None;
+                }
+                Ok(len) => self.pending.extend_from_slice(&buffer[..len]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    info!(error = ?e, client = ?self.addr, "gpsd client disconnected");
+                    return None;
+                }
+            }
+        }
+
+        let requests = Gpsd::parse_requests(&mut self.pending);
+
+        if self.pending.len() > GPSD_MAX_PENDING_REQUEST_LEN {
+            warn!(client = ?self.addr, "gpsd client request too long");
+            return None;
+        }
+
+        Some(requests)
+    }
+
+    /// Returns `false` once the client has disconnected.
+    fn write(&mut self, data: &str) -> bool {
+        match self.stream.write_all(data.as_bytes()) {
+            Ok(_) => true,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                warn!(client = ?self.addr, "gpsd client is not keeping up");
+                false
+            }
+            Err(e) => {
+                info!(error = ?e, client = ?self.addr, "gpsd client disconnected");
+                false
+            }
+        }
+    }
+}
+
+impl Drop for Gpsd {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpStream,
+        time::Duration,
+    };
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::broadcaster::{BroadcasterExt, Constellation, GpsdConfig, Satellite};
+
+    use super::{Gpsd, Request, WatchRequest};
+
+    #[test]
+    fn test_parse_requests() {
+        let mut pending = b"?WATCH={\"enable\":true,\"json\":true};?POLL;\n?VERSION;?DEV".to_vec();
+
+        let result = Gpsd::parse_requests(&mut pending);
+
+        assert_eq!(
+            result,
+            vec![
+                Request::Watch(WatchRequest { enable: true }),
+                Request::Poll,
+                Request::Version
+            ]
+        );
+        assert_eq!(pending, b"?DEV");
+    }
+
+    #[test]
+    fn test_parse_requests_watch() {
+        let mut pending = b"?WATCH;?WATCH={\"enable\":false};?WATCH={;?FOO;".to_vec();
+
+        let result = Gpsd::parse_requests(&mut pending);
+
+        assert_eq!(
+            result,
+            vec![
+                Request::Watch(WatchRequest { enable: true }),
+                Request::Watch(WatchRequest { enable: false }),
+                Request::Unknown("?WATCH={".to_string()),
+                Request::Unknown("?FOO".to_string()),
+            ]
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_tpv() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 2.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Gpsd::convert_gps_data_to_tpv(&date, &data);
+
+        assert_eq!(
+            Gpsd::serialize(&[super::Report::Tpv(result)]),
+            "{\"class\":\"TPV\",\"device\":\"msfs\",\"mode\":3,\"time\":\"2022-10-30T21:10:30.750Z\",\"lat\":51.509865,\"lon\":-0.118092,\"altMSL\":100.5,\"alt\":100.5,\"track\":2.55,\"magtrack\":357.55,\"magvar\":5.0,\"speed\":100.5}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_constellation_to_sky() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let constellation = Constellation {
+            satellites: vec![Satellite {
+                prn: 7,
+                elevation: 45.4,
+                azimuth: 120.6,
+                snr: 44,
+            }],
+            pdop: 1.5,
+            hdop: 0.9,
+            vdop: 1.2,
+        };
+
+        let result = Gpsd::convert_constellation_to_sky(&date, &constellation);
+
+        assert_eq!(
+            Gpsd::serialize(&[super::Report::Sky(result)]),
+            "{\"class\":\"SKY\",\"device\":\"msfs\",\"time\":\"2022-10-30T21:10:30.750Z\",\"hdop\":0.9,\"vdop\":1.2,\"pdop\":1.5,\"satellites\":[{\"PRN\":7,\"el\":45.0,\"az\":121.0,\"ss\":44.0,\"used\":true}]}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_watch() {
+        let config = GpsdConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+        };
+
+        let (mut server, addr) = Gpsd::bind(config).unwrap();
+
+        let idle = TcpStream::connect(addr).unwrap();
+        let watching = TcpStream::connect(addr).unwrap();
+        watching
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        // the handshake is answered before any data is sent
+        let mut lines = BufReader::new(watching.try_clone().unwrap()).lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .unwrap()
+            .starts_with("{\"class\":\"VERSION\""));
+
+        (&watching)
+            .write_all(b"?WATCH={\"enable\":true,\"json\":true};")
+            .unwrap();
+        for class in ["DEVICES", "WATCH"] {
+            assert!(lines
+                .next()
+                .unwrap()
+                .unwrap()
+                .starts_with(&format!("{{\"class\":\"{class}\"")));
+        }
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        server.send(data).unwrap();
+        assert_eq!(server.state.lock().unwrap().clients.len(), 2);
+
+        for class in ["TPV", "SKY"] {
+            assert!(lines
+                .next()
+                .unwrap()
+                .unwrap()
+                .starts_with(&format!("{{\"class\":\"{class}\"")));
+        }
+
+        drop(idle);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tracing::{error, info, instrument, trace, warn};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{BroadcasterExt, KmlConfig};
+
+const KML_ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+const KML_CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+const KML_REFRESH_INTERVAL_S: u32 = 1;
+
+const KML_CONTENT_TYPE: &str = "application/vnd.google-earth.kml+xml";
+const KML_LIVE_PATH: &str = "/live.kml";
+const KML_NETWORK_LINK_PATH: &str = "/network-link.kml";
+
+/// A local HTTP endpoint for Google Earth. `/network-link.kml` is opened once and refreshes `/live.kml`,
+/// which holds the aircraft placemark and the recent track.
+#[derive(Debug)]
+pub struct Kml {
+    state: Arc<Mutex<KmlState>>,
+    track_points: usize,
+    shutdown: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+struct KmlState {
+    position: Option<GpsData>,
+    heading: Option<f64>,
+    track: VecDeque<(f64, f64, f64)>,
+}
+
+impl Kml {
+    #[instrument(name = "Kml::new")]
+    pub fn new(config: KmlConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        Ok(Box::new(Self::bind(config)?.0))
+    }
+
+    fn bind(config: KmlConfig) -> Result<(Self, SocketAddr), io::Error> {
+        let listener = TcpListener::bind((config.bind.as_str(), config.port)).map_err(|e| {
+            error!(error = ?e, "failed to bind the KML HTTP listener");
+            e
+        })?;
+        listener.set_nonblocking(true).map_err(|e| {
+            error!(error = ?e, "failed to set the KML HTTP listener to non-blocking");
+            e
+        })?;
+        let addr = listener.local_addr()?;
+        let network_link = Self::network_link_document(addr);
+
+        let state = Arc::new(Mutex::new(KmlState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        // requests are served from their own thread, so Google Earth gets an answer even while the sim is paused
+        let server_state = state.clone();
+        let server_shutdown = shutdown.clone();
+        thread::Builder::new()
+            .name("kml".to_string())
+            .spawn(move || Self::serve(listener, server_state, network_link, server_shutdown))?;
+
+        info!(address = ?addr, "KML endpoint listening");
+
+        Ok((
+            Kml {
+                state,
+                track_points: config.track_points,
+                shutdown,
+            },
+            addr,
+        ))
+    }
+}
+
+impl BroadcasterExt for Kml {
+    #[instrument(name = "Kml::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        let mut state = self.state.lock().expect("the KML state lock is poisoned");
+
+        if state.track.len() >= self.track_points {
+            state.track.pop_front();
+        }
+        state.track.push_back((data.lon, data.lat, data.alt));
+        state.position.replace(data);
+
+        trace!("Successfully updated the KML");
+
+        Ok(())
+    }
+
+    #[instrument(name = "Kml::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        let mut state = self.state.lock().expect("the KML state lock is poisoned");
+        state.heading.replace(data.true_heading);
+
+        Ok(())
+    }
+}
+
+impl Kml {
+    fn serve(
+        listener: TcpListener,
+        state: Arc<Mutex<KmlState>>,
+        network_link: String,
+        shutdown: Arc<AtomicBool>,
+    ) {
+        while !shutdown.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    if let Err(e) = Self::respond(stream, &state, &network_link) {
+                        warn!(error = ?e, client = ?addr, "failed to answer the KML request");
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(KML_ACCEPT_INTERVAL)
+                }
+                Err(e) => {
+                    warn!(error = ?e, "failed to accept a KML client");
+                    thread::sleep(KML_ACCEPT_INTERVAL);
+                }
+            }
+        }
+
+        trace!("KML endpoint closed");
+    }
+
+    fn respond(
+        stream: TcpStream,
+        state: &Mutex<KmlState>,
+        network_link: &str,
+    ) -> Result<(), io::Error> {
+        // the listener is non-blocking, which some platforms pass on to the accepted streams
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(KML_CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(KML_CLIENT_TIMEOUT))?;
+
+        let mut reader = BufReader::new(stream);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // the headers are not needed, but they are read so the client does not see a reset connection
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+        let path = path.split('?').next().unwrap_or_default();
+
+        let response = match path {
+            KML_LIVE_PATH => {
+                let state = state.lock().expect("the KML state lock is poisoned");
+                Self::ok(&Self::live_document(&state))
+            }
+            "/" | KML_NETWORK_LINK_PATH => Self::ok(network_link),
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+
+        reader.into_inner().write_all(response.as_bytes())
+    }
+
+    fn ok(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+            KML_CONTENT_TYPE,
+            body.len(),
+            body
+        )
+    }
+
+    fn network_link_document(addr: SocketAddr) -> String {
+        // the link is absolute, as Google Earth resolves a relative one against the folder of a saved document
+        let host = match addr.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        };
+        let addr = SocketAddr::new(host, addr.port());
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n\
+             <NetworkLink>\n\
+             <name>MSFS 2020 GPS Link</name>\n\
+             <Link>\n\
+             <href>http://{}{}</href>\n\
+             <refreshMode>onInterval</refreshMode>\n\
+             <refreshInterval>{}</refreshInterval>\n\
+             </Link>\n\
+             </NetworkLink>\n\
+             </kml>\n",
+            addr, KML_LIVE_PATH, KML_REFRESH_INTERVAL_S
+        )
+    }
+
+    fn live_document(state: &KmlState) -> String {
+        let mut placemarks = String::new();
+
+        if state.track.len() > 1 {
+            let coordinates = state
+                .track
+                .iter()
+                .map(|(lon, lat, alt)| format!("{lon:.6},{lat:.6},{alt:.1}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            placemarks.push_str(&format!(
+                "<Placemark>\n\
+                 <name>Track</name>\n\
+                 <styleUrl>#track</styleUrl>\n\
+                 <LineString>\n\
+                 <altitudeMode>absolute</altitudeMode>\n\
+                 <coordinates>{coordinates}</coordinates>\n\
+                 </LineString>\n\
+                 </Placemark>\n"
+            ));
+        }
+
+        if let Some(position) = &state.position {
+            let heading = state.heading.unwrap_or(position.gps_ground_true_track);
+
+            placemarks.push_str(&format!(
+                "<Placemark>\n\
+                 <name>Aircraft</name>\n\
+                 <Style>\n\
+                 <IconStyle>\n\
+                 <heading>{:.1}</heading>\n\
+                 <Icon><href>http://maps.google.com/mapfiles/kml/shapes/airports.png</href></Icon>\n\
+                 </IconStyle>\n\
+                 </Style>\n\
+                 <Point>\n\
+                 <altitudeMode>absolute</altitudeMode>\n\
+                 <coordinates>{:.6},{:.6},{:.1}</coordinates>\n\
+                 </Point>\n\
+                 </Placemark>\n",
+                heading.rem_euclid(360.0),
+                position.lon,
+                position.lat,
+                position.alt
+            ));
+        }
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n\
+             <Document>\n\
+             <Style id=\"track\">\n\
+             <LineStyle>\n\
+             <color>ff00ffff</color>\n\
+             <width>3</width>\n\
+             </LineStyle>\n\
+             </Style>\n\
+             {placemarks}\
+             </Document>\n\
+             </kml>\n"
+        )
+    }
+}
+
+impl Drop for Kml {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{SocketAddr, TcpStream},
+    };
+
+    use crate::broadcaster::{BroadcasterExt, KmlConfig};
+
+    use super::{Kml, KmlState};
+
+    #[test]
+    fn test_network_link_document() {
+        let result = Kml::network_link_document("0.0.0.0:8080".parse().unwrap());
+        assert!(result.contains("<href>http://127.0.0.1:8080/live.kml</href>"));
+
+        let result = Kml::network_link_document("[::]:8080".parse().unwrap());
+        assert!(result.contains("<href>http://[::1]:8080/live.kml</href>"));
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: {addr}\r\n\r\n").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        response
+    }
+
+    #[test]
+    fn test_live_document() {
+        let mut state = KmlState::default();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        state.track.push_back((-0.11, 51.5, 90.0));
+        state.track.push_back((data.lon, data.lat, data.alt));
+        state.position.replace(data);
+        state.heading.replace(-10.0);
+
+        let result = Kml::live_document(&state);
+
+        assert!(result.contains(
+            "<coordinates>-0.110000,51.500000,90.0 -0.118092,51.509865,100.5</coordinates>"
+        ));
+        assert!(result.contains("<heading>350.0</heading>"));
+        assert!(result.contains("<coordinates>-0.118092,51.509865,100.5</coordinates>\n</Point>"));
+    }
+
+    #[test]
+    fn test_serve() {
+        let config = KmlConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+            track_points: 2,
+        };
+
+        let (mut kml, addr) = Kml::bind(config).unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        for _ in 0..3 {
+            kml.send(data.clone()).unwrap();
+        }
+        assert_eq!(kml.state.lock().unwrap().track.len(), 2);
+
+        let result = get(addr, "/network-link.kml");
+        assert!(result.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(result.contains(&format!("<href>http://{addr}/live.kml</href>")));
+        assert!(result.contains("<refreshInterval>1</refreshInterval>"));
+
+        let result = get(addr, "/live.kml?BBOX=-180,-90,180,90");
+        assert!(result.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(result.contains("Content-Type: application/vnd.google-earth.kml+xml\r\n"));
+        assert!(result.contains("<heading>310.6</heading>"));
+
+        let result = get(addr, "/favicon.ico");
+        assert!(result.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -1,9 +1,33 @@
 pub mod com;
 pub mod config;
+pub mod constellation;
+pub mod cot;
 pub mod ext;
+pub mod gdl90;
+pub mod gpsd;
+pub mod kml;
+pub mod mqtt;
+pub mod nmea;
+pub mod pty;
+pub mod tcp_server;
+pub mod ubx;
 pub mod udp;
+pub mod websocket;
+pub mod xplane;
 
 pub use com::*;
 pub use config::*;
+pub use constellation::*;
+pub use cot::*;
 pub use ext::*;
+pub use gdl90::*;
+pub use gpsd::*;
+pub use kml::*;
+pub use mqtt::*;
+pub use nmea::*;
+pub use pty::*;
+pub use tcp_server::*;
+pub use ubx::*;
 pub use udp::*;
+pub use websocket::*;
+pub use xplane::*;
@@ -0,0 +1,400 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use rumqttc::{Client, ConnectionError, Event, LastWill, MqttOptions, Outgoing, Packet, QoS};
+use tracing::{error, info, instrument, trace, warn};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{BroadcasterExt, MqttConfig};
+
+const MQTT_KEEP_ALIVE: Duration = Duration::from_secs(10);
+const MQTT_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+// enough for the connection thread to finish a reconnect delay
+const MQTT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const MQTT_SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// while the broker is unreachable, the positions that do not fit in the queue are dropped
+const MQTT_REQUEST_QUEUE_CAPACITY: usize = 32;
+
+const MQTT_STATUS_ONLINE: &str = "online";
+const MQTT_STATUS_OFFLINE: &str = "offline";
+
+pub struct Mqtt {
+    client: Client,
+    config: MqttConfig,
+    qos: QoS,
+    // set by the connection thread every time the broker accepts the connection
+    connected: Arc<AtomicBool>,
+    shutdown: Arc<AtomicBool>,
+    connection: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for Mqtt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mqtt")
+            .field("client", &"...")
+            .field("config", &self.config)
+            .field("qos", &self.qos)
+            .finish()
+    }
+}
+
+impl Mqtt {
+    #[instrument(name = "Mqtt::new")]
+    pub fn new(config: MqttConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        Ok(Box::new(Self::connect(config)?))
+    }
+
+    fn connect(config: MqttConfig) -> Result<Self, io::Error> {
+        let qos = rumqttc::qos(config.qos).map_err(|e| {
+            error!(error = ?e, "invalid MQTT QoS");
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the MQTT QoS must be 0, 1 or 2",
+            )
+        })?;
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(MQTT_KEEP_ALIVE);
+        options.set_last_will(LastWill::new(
+            &config.status_topic,
+            MQTT_STATUS_OFFLINE,
+            qos,
+            true,
+        ));
+
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+        }
+
+        let (client, mut connection) = Client::new(options, MQTT_REQUEST_QUEUE_CAPACITY);
+        let connected = Arc::new(AtomicBool::new(false));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let connection_connected = connected.clone();
+        let connection_shutdown = shutdown.clone();
+        let connection = thread::Builder::new()
+            .name("mqtt".to_string())
+            .spawn(move || {
+                // the iterator ends once the client has been dropped, unless the broker is unreachable,
+                // in which case the connection keeps reconnecting until the broadcaster shuts down
+                for notification in connection.iter() {
+                    match notification {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            info!("connected to the MQTT broker");
+                            connection_connected.store(true, Ordering::Relaxed);
+                        }
+                        Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(_) => (),
+                        Err(ConnectionError::RequestsDone) => break,
+                        Err(_) if connection_shutdown.load(Ordering::Relaxed) => break,
+                        Err(e) => {
+                            warn!(error = ?e, "MQTT connection error, reconnecting");
+                            thread::sleep(MQTT_RECONNECT_DELAY);
+                        }
+                    }
+                }
+
+                trace!("MQTT connection closed");
+            })?;
+
+        Ok(Mqtt {
+            client,
+            config,
+            qos,
+            connected,
+            shutdown,
+            connection: Some(connection),
+        })
+    }
+}
+
+impl BroadcasterExt for Mqtt {
+    #[instrument(name = "Mqtt::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        // the broker publishes the last will when the connection drops, so the status is restored on every connection
+        if self.connected.swap(false, Ordering::Relaxed) {
+            Self::publish(
+                &mut self.client,
+                self.qos,
+                &self.config.status_topic,
+                MQTT_STATUS_ONLINE,
+                true,
+            );
+        }
+
+        let retain = self.config.retain;
+        Self::publish(
+            &mut self.client,
+            self.qos,
+            &self.config.position_topic,
+            &Self::convert_gps_data_to_position(&data),
+            retain,
+        );
+        Self::publish(
+            &mut self.client,
+            self.qos,
+            &self.config.track_topic,
+            &format!("{:.1}", data.gps_ground_true_track),
+            retain,
+        );
+        Self::publish(
+            &mut self.client,
+            self.qos,
+            &self.config.speed_topic,
+            &format!("{:.1}", data.gps_ground_speed_in_knots()),
+            retain,
+        );
+
+        trace!("Successfully published over MQTT");
+
+        Ok(())
+    }
+}
+
+impl Mqtt {
+    /// Failures are only logged, as the connection thread keeps reconnecting to the broker.
+    fn publish(client: &mut Client, qos: QoS, topic: &str, payload: &str, retain: bool) {
+        if let Err(e) = client.try_publish(topic, qos, retain, payload) {
+            warn!(error = ?e, topic = ?topic, "failed to publish over MQTT");
+        }
+    }
+
+    /// Returns `false` if the connection thread has not stopped in time, in which case it is left to finish on its own.
+    fn close(&mut self) -> bool {
+        let Some(connection) = self.connection.take() else {
+            return true;
+        };
+
+        // a clean disconnect does not trigger the last will
+        Self::publish(
+            &mut self.client,
+            self.qos,
+            &self.config.status_topic,
+            MQTT_STATUS_OFFLINE,
+            true,
+        );
+
+        if let Err(e) = self.client.try_disconnect() {
+            warn!(error = ?e, "failed to disconnect from the MQTT broker");
+        }
+
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        let deadline = Instant::now() + MQTT_SHUTDOWN_TIMEOUT;
+        while !connection.is_finished() {
+            if Instant::now() >= deadline {
+                warn!("the MQTT connection did not close in time");
+                return false;
+            }
+
+            thread::sleep(MQTT_SHUTDOWN_POLL_INTERVAL);
+        }
+
+        if connection.join().is_err() {
+            error!("the MQTT connection thread panicked");
+        }
+
+        true
+    }
+
+    fn convert_gps_data_to_position(data: &GpsData) -> String {
+        serde_json::json!({
+            "lat": data.lat,
+            "lon": data.lon,
+            "alt": data.alt,
+        })
+        .to_string()
+    }
+}
+
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::broadcaster::{BroadcasterExt, MqttConfig};
+
+    use super::Mqtt;
+
+    fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut header = [0u8; 1];
+        stream.read_exact(&mut header).unwrap();
+
+        let mut remaining_length = 0usize;
+        for shift in (0..4).map(|i| i * 7) {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).unwrap();
+            remaining_length |= ((byte[0] & 0x7F) as usize) << shift;
+
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+
+        let mut body = vec![0u8; remaining_length];
+        stream.read_exact(&mut body).unwrap();
+
+        (header[0], body)
+    }
+
+    fn read_publish(stream: &mut TcpStream) -> (String, String, bool) {
+        let (header, body) = read_packet(stream);
+        assert_eq!(header >> 4, 3);
+
+        let topic_length = u16::from_be_bytes([body[0], body[1]]) as usize;
+        let topic = String::from_utf8(body[2..2 + topic_length].to_vec()).unwrap();
+        let payload = String::from_utf8(body[2 + topic_length..].to_vec()).unwrap();
+
+        (topic, payload, header & 0x01 == 0x01)
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_position() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Mqtt::convert_gps_data_to_position(&data);
+
+        assert_eq!(
+            result,
+            "{\"alt\":100.5,\"lat\":51.509865,\"lon\":-0.118092}"
+        );
+    }
+
+    #[test]
+    fn test_publish() {
+        let broker = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = MqttConfig {
+            host: "127.0.0.1".to_string(),
+            port: broker.local_addr().unwrap().port(),
+            client_id: "msfs-2020-gps-link".to_string(),
+            username: None,
+            password: None,
+            qos: 0,
+            retain: true,
+            position_topic: "msfs/position".to_string(),
+            track_topic: "msfs/track".to_string(),
+            speed_topic: "msfs/speed".to_string(),
+            status_topic: "msfs/status".to_string(),
+        };
+
+        let mut mqtt = Mqtt::connect(config).unwrap();
+
+        let (mut stream, _) = broker.accept().unwrap();
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(header >> 4, 1);
+        // the will flag, a QoS 0 will and the will retain flag
+        assert_eq!(body[7] & 0b0011_1100, 0b0010_0100);
+        assert!(body.ends_with(b"\x00\x0bmsfs/status\x00\x07offline"));
+
+        // CONNACK, connection accepted
+        stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        // the status is only published once the connection has been accepted
+        loop {
+            mqtt.send(data.clone()).unwrap();
+
+            let (topic, payload, retain) = read_publish(&mut stream);
+            if topic == "msfs/status" {
+                assert_eq!(payload, "online");
+                assert!(retain);
+                break;
+            }
+
+            // the track and speed sent along with the position
+            read_publish(&mut stream);
+            read_publish(&mut stream);
+        }
+
+        assert_eq!(
+            read_publish(&mut stream),
+            (
+                "msfs/position".to_string(),
+                "{\"alt\":100.5,\"lat\":51.509865,\"lon\":-0.118092}".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            read_publish(&mut stream),
+            ("msfs/track".to_string(), "310.6".to_string(), true)
+        );
+        assert_eq!(
+            read_publish(&mut stream),
+            ("msfs/speed".to_string(), "195.4".to_string(), true)
+        );
+
+        drop(mqtt);
+
+        assert_eq!(
+            read_publish(&mut stream),
+            ("msfs/status".to_string(), "offline".to_string(), true)
+        );
+        // DISCONNECT
+        assert_eq!(read_packet(&mut stream).0 >> 4, 14);
+    }
+
+    #[test]
+    fn test_drop_without_broker() {
+        // a port that nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let config = MqttConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            client_id: "msfs-2020-gps-link".to_string(),
+            username: None,
+            password: None,
+            qos: 0,
+            retain: true,
+            position_topic: "msfs/position".to_string(),
+            track_topic: "msfs/track".to_string(),
+            speed_topic: "msfs/speed".to_string(),
+            status_topic: "msfs/status".to_string(),
+        };
+
+        let mut mqtt = Mqtt::connect(config).unwrap();
+
+        assert!(mqtt.close(), "the MQTT connection thread leaked");
+    }
+}
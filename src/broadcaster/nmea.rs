@@ -0,0 +1,681 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{Constellation, NmeaConfig, NmeaProfile, NmeaSentence};
+
+const NMEA_MID_GSA_INTERVAL_S: u64 = 1;
+const NMEA_MID_GSA_MAX_SATELLITES: usize = 12;
+const NMEA_MID_GSV_SATELLITES_PER_MESSAGE: usize = 4;
+// the user equivalent range error used to turn the dilution of precision into an estimated position error
+const NMEA_GARMIN_USER_EQUIVALENT_RANGE_ERROR_M: f64 = 2.0;
+const FEET_PER_METER: f64 = 3.28084;
+
+/// Generates the NMEA 0183 sentences of the broadcasters that emulate a GPS receiver.
+#[derive(Debug)]
+pub struct Nmea {
+    sentences: Vec<NmeaSentence>,
+    profile: NmeaProfile,
+    last_mid_gsa: Option<SystemTime>,
+    last_gps_magnetic_variation: Option<f64>,
+}
+
+impl Nmea {
+    pub fn new(config: NmeaConfig) -> Self {
+        Self {
+            sentences: config.sentences,
+            profile: config.profile,
+            last_mid_gsa: None,
+            last_gps_magnetic_variation: None,
+        }
+    }
+
+    pub fn messages(&mut self, data: &GpsData) -> Vec<String> {
+        let date = Utc::now();
+        let constellation = Constellation::visible_from(&date, data);
+        let send_mid_gsa = self.should_send_mid_gsa();
+
+        let mut messages = Vec::new();
+
+        for sentence in &self.sentences {
+            match sentence {
+                NmeaSentence::Gga => messages.push(Self::convert_gps_data_to_nmea_mid_gga(
+                    &date,
+                    data,
+                    &constellation,
+                    self.profile,
+                )),
+                NmeaSentence::Rmc => {
+                    messages.push(Self::convert_gps_data_to_nmea_mid_rmc(&date, data))
+                }
+                NmeaSentence::Gsa if send_mid_gsa => {
+                    messages.push(Self::convert_constellation_to_nmea_mid_gsa(&constellation))
+                }
+                NmeaSentence::Gsv if send_mid_gsa => {
+                    messages.extend(Self::convert_constellation_to_nmea_mid_gsv(&constellation))
+                }
+                NmeaSentence::Vtg => messages.push(Self::convert_gps_data_to_nmea_mid_vtg(data)),
+                NmeaSentence::Gll => {
+                    messages.push(Self::convert_gps_data_to_nmea_mid_gll(&date, data))
+                }
+                NmeaSentence::Zda => messages.push(Self::convert_date_to_nmea_mid_zda(&date)),
+                // the GSA and GSV sentences are throttled
+                // and the heading sentences are sent along with the attitude data
+                NmeaSentence::Gsa | NmeaSentence::Gsv | NmeaSentence::Hdt | NmeaSentence::Hdg => (),
+            }
+        }
+
+        if self.profile == NmeaProfile::Garmin {
+            messages.push(Self::convert_gps_data_to_nmea_garmin_pgrmz(data));
+
+            if send_mid_gsa {
+                messages.push(Self::convert_constellation_to_nmea_garmin_pgrme(
+                    &constellation,
+                ));
+                messages.push(Self::nmea_garmin_pgrmm());
+            }
+        }
+
+        if send_mid_gsa {
+            self.last_mid_gsa.replace(SystemTime::now());
+        }
+
+        self.last_gps_magnetic_variation
+            .replace(data.gps_magnetic_variation);
+
+        messages
+    }
+
+    pub fn attitude_messages(&self, data: &AttitudeData) -> Vec<String> {
+        let mut messages = Vec::new();
+
+        for sentence in &self.sentences {
+            match sentence {
+                NmeaSentence::Hdt => {
+                    messages.push(Self::convert_attitude_data_to_nmea_mid_hdt(data))
+                }
+                NmeaSentence::Hdg => {
+                    // the magnetic heading can only be computed once the magnetic variation is known
+                    if let Some(magnetic_variation) = self.last_gps_magnetic_variation {
+                        messages.push(Self::convert_attitude_data_to_nmea_mid_hdg(
+                            data,
+                            magnetic_variation,
+                        ))
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        messages
+    }
+}
+
+impl Nmea {
+    fn should_send_mid_gsa(&self) -> bool {
+        match self.last_mid_gsa {
+            Some(last_mid_gsa) => {
+                if let Ok(elapsed) = last_mid_gsa.elapsed() {
+                    if elapsed.as_secs() >= NMEA_MID_GSA_INTERVAL_S {
+                        return true;
+                    }
+                }
+            }
+            None => {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn convert_gps_data_to_nmea_mid_gga(
+        date: &DateTime<Utc>,
+        data: &GpsData,
+        constellation: &Constellation,
+        profile: NmeaProfile,
+    ) -> String {
+        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
+        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
+
+        // the Garmin profile carries the pressure altitude in PGRMZ, which leaves GGA free for the geometric altitude
+        let altitude = match profile {
+            NmeaProfile::Standard => data.pressure_altitude,
+            NmeaProfile::Garmin => data.alt,
+        };
+
+        let message = format!(
+            "$GPGGA,{},{:0>2}{:0>7.4},{},{:0>3}{:0>7.4},{},1,{:0>2},{:.1},{:.1},M,0.0,M,,",
+            date.format("%H%M%S%.3f"),
+            lat_deg,
+            lat_min,
+            lat_dir,
+            lon_deg,
+            lon_min,
+            lon_dir,
+            constellation.satellites.len(),
+            constellation.hdop,
+            altitude
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_gps_data_to_nmea_mid_rmc(date: &DateTime<Utc>, data: &GpsData) -> String {
+        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
+        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
+        let (magnetic_variation, magnetic_variation_dir) = data.gps_magnetic_variation_as_abs_dir();
+
+        let message = format!(
+            "$GPRMC,{},A,{:0>2}{:0>7.4},{},{:0>3}{:0>7.4},{},{:.2},{:.2},{},{:.1},{},S",
+            date.format("%H%M%S%.3f"),
+            lat_deg,
+            lat_min,
+            lat_dir,
+            lon_deg,
+            lon_min,
+            lon_dir,
+            data.gps_ground_speed_in_knots(),
+            data.gps_ground_true_track,
+            date.format("%d%m%y"),
+            magnetic_variation,
+            magnetic_variation_dir,
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_gps_data_to_nmea_mid_vtg(data: &GpsData) -> String {
+        let magnetic_track =
+            (data.gps_ground_true_track - data.gps_magnetic_variation).rem_euclid(360.0);
+        let speed_in_knots = data.gps_ground_speed_in_knots();
+
+        let message = format!(
+            "$GPVTG,{:.2},T,{:.2},M,{:.2},N,{:.2},K,A",
+            data.gps_ground_true_track,
+            magnetic_track,
+            speed_in_knots,
+            data.gps_ground_speed * 3.6,
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_gps_data_to_nmea_mid_gll(date: &DateTime<Utc>, data: &GpsData) -> String {
+        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
+        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
+
+        let message = format!(
+            "$GPGLL,{:0>2}{:0>7.4},{},{:0>3}{:0>7.4},{},{},A,A",
+            lat_deg,
+            lat_min,
+            lat_dir,
+            lon_deg,
+            lon_min,
+            lon_dir,
+            date.format("%H%M%S%.3f"),
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_date_to_nmea_mid_zda(date: &DateTime<Utc>) -> String {
+        let message = format!("$GPZDA,{},00,00", date.format("%H%M%S%.3f,%d,%m,%Y"));
+
+        Self::sentence(message)
+    }
+
+    fn convert_attitude_data_to_nmea_mid_hdt(data: &AttitudeData) -> String {
+        let message = format!("$GPHDT,{:.1},T", data.true_heading.rem_euclid(360.0));
+
+        Self::sentence(message)
+    }
+
+    fn convert_attitude_data_to_nmea_mid_hdg(
+        data: &AttitudeData,
+        gps_magnetic_variation: f64,
+    ) -> String {
+        let magnetic_heading = (data.true_heading - gps_magnetic_variation).rem_euclid(360.0);
+        let magnetic_variation_dir = if gps_magnetic_variation >= 0.0 {
+            "E"
+        } else {
+            "W"
+        };
+
+        let message = format!(
+            "$GPHDG,{:.1},,,{:.1},{}",
+            magnetic_heading,
+            gps_magnetic_variation.abs(),
+            magnetic_variation_dir
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_gps_data_to_nmea_garmin_pgrmz(data: &GpsData) -> String {
+        let message = format!("$PGRMZ,{:.0},f,3", data.pressure_altitude * FEET_PER_METER);
+
+        Self::sentence(message)
+    }
+
+    fn convert_constellation_to_nmea_garmin_pgrme(constellation: &Constellation) -> String {
+        let horizontal_error = constellation.hdop * NMEA_GARMIN_USER_EQUIVALENT_RANGE_ERROR_M;
+        let vertical_error = constellation.vdop * NMEA_GARMIN_USER_EQUIVALENT_RANGE_ERROR_M;
+        let position_error = horizontal_error.hypot(vertical_error);
+
+        let message = format!(
+            "$PGRME,{:.1},M,{:.1},M,{:.1},M",
+            horizontal_error, vertical_error, position_error
+        );
+
+        Self::sentence(message)
+    }
+
+    fn nmea_garmin_pgrmm() -> String {
+        Self::sentence("$PGRMM,WGS 84".to_string())
+    }
+
+    /// Appends the checksum, which XORs every character between `$` and `*`, and the line ending.
+    fn sentence(message: String) -> String {
+        let checksum = message.chars().skip(1).fold(0u8, |acc, c| acc ^ c as u8);
+
+        format!("{message}*{checksum:02X}\r\n")
+    }
+
+    fn convert_constellation_to_nmea_mid_gsa(constellation: &Constellation) -> String {
+        let prns = (0..NMEA_MID_GSA_MAX_SATELLITES)
+            .map(|i| match constellation.satellites.get(i) {
+                Some(satellite) => format!("{:0>2}", satellite.prn),
+                None => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mode = if constellation.satellites.len() >= 4 {
+            3
+        } else {
+            1
+        };
+
+        let message = format!(
+            "$GPGSA,A,{},{},{:.1},{:.1},{:.1}",
+            mode, prns, constellation.pdop, constellation.hdop, constellation.vdop
+        );
+
+        Self::sentence(message)
+    }
+
+    fn convert_constellation_to_nmea_mid_gsv(constellation: &Constellation) -> Vec<String> {
+        let satellites_in_view = constellation.satellites.len();
+
+        let mut chunks = constellation
+            .satellites
+            .chunks(NMEA_MID_GSV_SATELLITES_PER_MESSAGE)
+            .collect::<Vec<_>>();
+
+        // with no satellites in view, a single GSV message without satellite details is sent
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+
+        let total_messages = chunks.len();
+
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, satellites)| {
+                let details = satellites
+                    .iter()
+                    .map(|satellite| {
+                        format!(
+                            ",{:0>2},{:0>2},{:0>3},{:0>2}",
+                            satellite.prn,
+                            satellite.elevation.round() as u8,
+                            satellite.azimuth.round() as u16 % 360,
+                            satellite.snr
+                        )
+                    })
+                    .collect::<String>();
+
+                let message = format!(
+                    "$GPGSV,{},{},{:0>2}{}",
+                    total_messages,
+                    i + 1,
+                    satellites_in_view,
+                    details
+                );
+
+                Self::sentence(message)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::Nmea;
+    use crate::broadcaster::{Constellation, NmeaProfile, Satellite};
+
+    fn constellation(satellites: usize) -> Constellation {
+        Constellation {
+            satellites: (1..=satellites as u8)
+                .map(|prn| Satellite {
+                    prn,
+                    elevation: 90.0 - f64::from(prn) * 7.0,
+                    azimuth: f64::from(prn) * 29.6,
+                    snr: 50 - prn,
+                })
+                .collect(),
+            pdop: 1.4,
+            hdop: 1.0,
+            vdop: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_gga() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_gga(
+            &date,
+            &data,
+            &constellation(12),
+            NmeaProfile::Standard,
+        );
+
+        assert_eq!(
+            result,
+            "$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,3.0,M,0.0,M,,*70\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_gga_2() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 3)
+            .and_then(|d| d.and_hms_milli_opt(2, 1, 3, 75))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 0.00040752447554520855,
+            lon: 0.01397450300629543,
+            alt: 0.0,
+            pressure_altitude: 0.9642891859251844,
+            gps_ground_true_track: 92.71680515837362,
+            gps_magnetic_variation: -4.384223296150313,
+            gps_ground_speed: 0.0,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_gga(
+            &date,
+            &data,
+            &constellation(12),
+            NmeaProfile::Standard,
+        );
+
+        assert_eq!(
+            result,
+            "$GPGGA,020103.075,0000.0245,N,00000.8385,E,1,12,1.0,1.0,M,0.0,M,,*68\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_rmc() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_rmc(&date, &data);
+
+        assert_eq!(
+            result,
+            "$GPRMC,211030.750,A,5130.5919,N,00007.0855,W,195.36,310.55,301022,5.0,E,S*07\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_rmc_2() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 3)
+            .and_then(|d| d.and_hms_milli_opt(2, 1, 3, 75))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 0.00040752447554520855,
+            lon: 0.01397450300629543,
+            alt: 0.0,
+            pressure_altitude: 0.9642891859251844,
+            gps_ground_true_track: 92.71680515837362,
+            gps_magnetic_variation: -4.384223296150313,
+            gps_ground_speed: 0.0,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_rmc(&date, &data);
+
+        assert_eq!(
+            result,
+            "$GPRMC,020103.075,A,0000.0245,N,00000.8385,E,0.00,92.72,030122,4.4,W,S*3E\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_constellation_to_nmea_mid_gsa() {
+        let result = Nmea::convert_constellation_to_nmea_mid_gsa(&constellation(9));
+
+        assert_eq!(
+            result,
+            "$GPGSA,A,3,01,02,03,04,05,06,07,08,09,,,,1.4,1.0,1.0*36\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_constellation_to_nmea_mid_gsv() {
+        let result = Nmea::convert_constellation_to_nmea_mid_gsv(&constellation(9));
+
+        assert_eq!(
+            result,
+            vec![
+                "$GPGSV,3,1,09,01,83,030,49,02,76,059,48,03,69,089,47,04,62,118,46*71\r\n",
+                "$GPGSV,3,2,09,05,55,148,45,06,48,178,44,07,41,207,43,08,34,237,42*73\r\n",
+                "$GPGSV,3,3,09,09,27,266,41*4B\r\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_constellation_to_nmea_mid_gsv_no_satellites() {
+        let result = Nmea::convert_constellation_to_nmea_mid_gsv(&constellation(0));
+
+        assert_eq!(result, vec!["$GPGSV,1,1,00*79\r\n"]);
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_vtg() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_vtg(&data);
+
+        assert_eq!(
+            result,
+            "$GPVTG,310.55,T,305.55,M,195.36,N,361.80,K,A*23\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_gll() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_gll(&date, &data);
+
+        assert_eq!(
+            result,
+            "$GPGLL,5130.5919,N,00007.0855,W,211030.750,A,A*44\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_date_to_nmea_mid_zda() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let result = Nmea::convert_date_to_nmea_mid_zda(&date);
+
+        assert_eq!(result, "$GPZDA,211030.750,30,10,2022,00,00*55\r\n");
+    }
+
+    #[test]
+    fn test_convert_attitude_data_to_nmea_mid_hdt() {
+        let data = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 310.55,
+            pitch: 0.0,
+            bank: 0.0,
+        };
+
+        let result = Nmea::convert_attitude_data_to_nmea_mid_hdt(&data);
+
+        assert_eq!(result, "$GPHDT,310.6,T*31\r\n");
+    }
+
+    #[test]
+    fn test_convert_attitude_data_to_nmea_mid_hdg() {
+        let data = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 92.7,
+            pitch: 0.0,
+            bank: 0.0,
+        };
+
+        let result = Nmea::convert_attitude_data_to_nmea_mid_hdg(&data, -4.384223296150313);
+
+        assert_eq!(result, "$GPHDG,97.1,,,4.4,W*18\r\n");
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_mid_gga_garmin() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 950.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_mid_gga(
+            &date,
+            &data,
+            &constellation(12),
+            NmeaProfile::Garmin,
+        );
+
+        assert_eq!(
+            result,
+            "$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,1000.0,M,0.0,M,,*42\r\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_gps_data_to_nmea_garmin_pgrmz() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 950.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Nmea::convert_gps_data_to_nmea_garmin_pgrmz(&data);
+
+        assert_eq!(result, "$PGRMZ,3117,f,3*2F\r\n");
+    }
+
+    #[test]
+    fn test_convert_constellation_to_nmea_garmin_pgrme() {
+        let result = Nmea::convert_constellation_to_nmea_garmin_pgrme(&Constellation {
+            vdop: 1.5,
+            ..constellation(9)
+        });
+
+        assert_eq!(result, "$PGRME,2.0,M,3.0,M,3.6,M*2A\r\n");
+    }
+
+    #[test]
+    fn test_nmea_garmin_pgrmm() {
+        let result = Nmea::nmea_garmin_pgrmm();
+
+        assert_eq!(result, "$PGRMM,WGS 84*06\r\n");
+    }
+}
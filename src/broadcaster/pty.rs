@@ -0,0 +1,78 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serialport::SerialPort;
+
+/// A pseudo-terminal pair that local apps can open through a stable symlink, as if it was a serial port.
+#[derive(Debug)]
+#[cfg_attr(not(unix), allow(dead_code))]
+pub struct Pty {
+    link: PathBuf,
+    #[cfg(unix)]
+    slave: serialport::TTYPort,
+}
+
+#[cfg(unix)]
+impl Pty {
+    /// Returns the master end, which the NMEA sentences are written to.
+    pub fn open(link: &Path) -> Result<(Box<dyn SerialPort>, Pty), io::Error> {
+        use std::{fs, os::unix::fs::symlink};
+
+        let (master, slave) = serialport::TTYPort::pair()?;
+        let slave_path = slave.name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "the pseudo-terminal has no name")
+        })?;
+
+        // a link left behind by a previous session is replaced, but never a regular file
+        match fs::symlink_metadata(link) {
+            Ok(metadata) if metadata.file_type().is_symlink() => fs::remove_file(link)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "the pseudo-terminal link path already exists and is not a symlink",
+                ))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+
+        symlink(slave_path, link)?;
+
+        Ok((
+            Box::new(master),
+            Pty {
+                link: link.to_path_buf(),
+                slave,
+            },
+        ))
+    }
+
+    /// Discards the sentences no app has read, which would otherwise block the writes once the buffer is full.
+    pub fn discard_unread(&mut self) -> Result<(), io::Error> {
+        self.slave.clear(serialport::ClearBuffer::Input)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+impl Pty {
+    pub fn open(_link: &Path) -> Result<(Box<dyn SerialPort>, Pty), io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pseudo-terminals are only supported on Unix",
+        ))
+    }
+
+    pub fn discard_unread(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.link);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+use tracing::{error, info, instrument, trace, warn};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{BroadcasterExt, Nmea, TcpServerConfig};
+
+const TCP_SERVER_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub struct TcpServer {
+    listener: TcpListener,
+    clients: Vec<(SocketAddr, TcpStream)>,
+    nmea: Nmea,
+}
+
+impl TcpServer {
+    #[instrument(name = "TcpServer::new")]
+    pub fn new(config: TcpServerConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        Ok(Box::new(Self::bind(config)?))
+    }
+
+    fn bind(config: TcpServerConfig) -> Result<Self, io::Error> {
+        let listener = TcpListener::bind((config.bind.as_str(), config.port)).map_err(|e| {
+            error!(error = ?e, "failed to bind the TCP listener");
+            e
+        })?;
+        listener.set_nonblocking(true).map_err(|e| {
+            error!(error = ?e, "failed to set the TCP listener to non-blocking");
+            e
+        })?;
+
+        Ok(TcpServer {
+            listener,
+            clients: Vec::new(),
+            nmea: Nmea::new(config.nmea),
+        })
+    }
+}
+
+impl BroadcasterExt for TcpServer {
+    #[instrument(name = "TcpServer::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        self.accept_clients();
+
+        let messages = self.nmea.messages(&data);
+        self.write(&messages.concat());
+
+        trace!("Successfully sent broadcast over TCP");
+
+        Ok(())
+    }
+
+    #[instrument(name = "TcpServer::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        let messages = self.nmea.attitude_messages(&data);
+        self.write(&messages.concat());
+
+        Ok(())
+    }
+}
+
+impl TcpServer {
+    fn accept_clients(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    // on Windows, the accepted stream inherits the non-blocking mode of the listener
+                    let result = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_write_timeout(Some(TCP_SERVER_WRITE_TIMEOUT)));
+
+                    if let Err(e) = result {
+                        warn!(error = ?e, client = ?addr, "failed to set up the TCP client");
+                        continue;
+                    }
+
+                    info!(client = ?addr, "TCP client connected");
+                    self.clients.push((addr, stream));
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!(error = ?e, "failed to accept a TCP client");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Writes to every connected client. A client that fails is disconnected without affecting the others,
+    /// because a timed out write may have sent part of a sentence and the next one would follow it.
+    fn write(&mut self, data: &str) {
+        if data.is_empty() {
+            return;
+        }
+
+        self.clients
+            .retain_mut(|(addr, stream)| match stream.write_all(data.as_bytes()) {
+                Ok(_) => true,
+                Err(ref e)
+                    if e.kind() == io::ErrorKind::TimedOut
+                        || e.kind() == io::ErrorKind::WouldBlock =>
+                {
+                    warn!(client = ?addr, "TCP client is not keeping up");
+                    false
+                }
+                Err(e) => {
+                    info!(error = ?e, client = ?addr, "TCP client disconnected");
+                    false
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpStream,
+        thread,
+        time::Duration,
+    };
+
+    use crate::broadcaster::{NmeaConfig, NmeaProfile, NmeaSentence, TcpServerConfig};
+
+    use super::{BroadcasterExt, TcpServer};
+
+    #[test]
+    fn test_send_to_connected_clients() {
+        let config = TcpServerConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+            nmea: NmeaConfig {
+                sentences: vec![NmeaSentence::Gga, NmeaSentence::Rmc],
+                profile: NmeaProfile::Standard,
+            },
+        };
+
+        let mut server = TcpServer::bind(config).unwrap();
+        let addr = server.listener.local_addr().unwrap();
+
+        let first = TcpStream::connect(addr).unwrap();
+        let second = TcpStream::connect(addr).unwrap();
+        // a client that disconnects must not affect the others
+        drop(TcpStream::connect(addr).unwrap());
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        server.send(data.clone()).unwrap();
+        assert_eq!(server.clients.len(), 3);
+
+        // the write that follows the reset of the dropped client fails
+        for _ in 0..50 {
+            if server.clients.len() < 3 {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+            server.send(data.clone()).unwrap();
+        }
+        assert_eq!(server.clients.len(), 2);
+
+        for stream in [first, second] {
+            let mut lines = BufReader::new(stream).lines();
+
+            assert!(lines.next().unwrap().unwrap().starts_with("$GPGGA,"));
+            assert!(lines.next().unwrap().unwrap().starts_with("$GPRMC,"));
+        }
+    }
+}
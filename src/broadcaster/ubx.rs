@@ -0,0 +1,322 @@
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::Constellation;
+
+const UBX_SYNC_CHAR_1: u8 = 0xB5;
+const UBX_SYNC_CHAR_2: u8 = 0x62;
+
+const UBX_CLASS_NAV: u8 = 0x01;
+const UBX_ID_NAV_POSLLH: u8 = 0x02;
+const UBX_ID_NAV_PVT: u8 = 0x07;
+const UBX_ID_NAV_VELNED: u8 = 0x12;
+
+// the GPS week starts on 1980-01-06 and GPS time is ahead of UTC by the leap seconds since then
+const GPS_EPOCH_UNIX_MS: i64 = 315_964_800_000;
+const GPS_LEAP_SECONDS_MS: i64 = 18_000;
+const GPS_WEEK_MS: i64 = 604_800_000;
+
+const UBX_FIX_TYPE_3D: u8 = 3;
+// the same user equivalent range error the Garmin NMEA profile uses for its estimated position error
+const UBX_USER_EQUIVALENT_RANGE_ERROR_M: f64 = 2.0;
+const UBX_TIME_ACCURACY_NS: u32 = 20;
+const UBX_SPEED_ACCURACY_MM_S: u32 = 100;
+const UBX_HEADING_ACCURACY_DEG: f64 = 1.0;
+
+/// Generates the u-blox UBX navigation messages of the broadcasters that emulate a u-blox receiver.
+#[derive(Debug)]
+pub struct Ubx;
+
+impl Ubx {
+    pub fn messages(data: &GpsData) -> Vec<Vec<u8>> {
+        let date = Utc::now();
+        let constellation = Constellation::visible_from(&date, data);
+
+        vec![
+            Self::frame(
+                UBX_CLASS_NAV,
+                UBX_ID_NAV_PVT,
+                &Self::nav_pvt_payload(&date, data, &constellation),
+            ),
+            Self::frame(
+                UBX_CLASS_NAV,
+                UBX_ID_NAV_POSLLH,
+                &Self::nav_posllh_payload(&date, data, &constellation),
+            ),
+            Self::frame(
+                UBX_CLASS_NAV,
+                UBX_ID_NAV_VELNED,
+                &Self::nav_velned_payload(&date, data),
+            ),
+        ]
+    }
+
+    fn nav_pvt_payload(
+        date: &DateTime<Utc>,
+        data: &GpsData,
+        constellation: &Constellation,
+    ) -> Vec<u8> {
+        let (velocity_north, velocity_east, velocity_down) = Self::velocity_ned(data);
+        let (horizontal_accuracy, vertical_accuracy) = Self::position_accuracy(constellation);
+
+        // validDate, validTime, fullyResolved and validMag
+        let valid = 0b0000_1111;
+        // gnssFixOK
+        let flags = 0b0000_0001;
+        // confirmedAvai, confirmedDate and confirmedTime
+        let flags2 = 0b1110_0000;
+
+        let mut payload = Vec::with_capacity(92);
+
+        payload.extend_from_slice(&Self::gps_time_of_week_ms(date).to_le_bytes());
+        payload.extend_from_slice(&(date.year() as u16).to_le_bytes());
+        payload.push(date.month() as u8);
+        payload.push(date.day() as u8);
+        payload.push(date.hour() as u8);
+        payload.push(date.minute() as u8);
+        payload.push(date.second() as u8);
+        payload.push(valid);
+        payload.extend_from_slice(&UBX_TIME_ACCURACY_NS.to_le_bytes());
+        payload.extend_from_slice(&(date.timestamp_subsec_nanos() as i32).to_le_bytes());
+        payload.push(UBX_FIX_TYPE_3D);
+        payload.push(flags);
+        payload.push(flags2);
+        payload.push(constellation.satellites.len() as u8);
+        payload.extend_from_slice(&Self::encode_degrees(data.lon, 1e7).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_degrees(data.lat, 1e7).to_le_bytes());
+        // the simulator does not model the geoid, so the height above the ellipsoid is the altitude above mean sea level
+        payload.extend_from_slice(&Self::encode_millimeters(data.alt).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(data.alt).to_le_bytes());
+        payload.extend_from_slice(&horizontal_accuracy.to_le_bytes());
+        payload.extend_from_slice(&vertical_accuracy.to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(velocity_north).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(velocity_east).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(velocity_down).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(data.gps_ground_speed).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_heading(data.gps_ground_true_track).to_le_bytes());
+        payload.extend_from_slice(&UBX_SPEED_ACCURACY_MM_S.to_le_bytes());
+        payload.extend_from_slice(
+            &(Self::encode_heading(UBX_HEADING_ACCURACY_DEG) as u32).to_le_bytes(),
+        );
+        payload.extend_from_slice(&((constellation.pdop * 100.0).round() as u16).to_le_bytes());
+        // flags3 and reserved
+        payload.extend_from_slice(&[0x00; 6]);
+        // the heading of the vehicle is not known
+        payload.extend_from_slice(&0i32.to_le_bytes());
+        payload.extend_from_slice(
+            &((data.gps_magnetic_variation * 100.0).round() as i16).to_le_bytes(),
+        );
+        payload.extend_from_slice(&0u16.to_le_bytes());
+
+        payload
+    }
+
+    fn nav_posllh_payload(
+        date: &DateTime<Utc>,
+        data: &GpsData,
+        constellation: &Constellation,
+    ) -> Vec<u8> {
+        let (horizontal_accuracy, vertical_accuracy) = Self::position_accuracy(constellation);
+
+        let mut payload = Vec::with_capacity(28);
+
+        payload.extend_from_slice(&Self::gps_time_of_week_ms(date).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_degrees(data.lon, 1e7).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_degrees(data.lat, 1e7).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(data.alt).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_millimeters(data.alt).to_le_bytes());
+        payload.extend_from_slice(&horizontal_accuracy.to_le_bytes());
+        payload.extend_from_slice(&vertical_accuracy.to_le_bytes());
+
+        payload
+    }
+
+    /// NAV-VELNED reports the velocities in cm/s rather than mm/s.
+    fn nav_velned_payload(date: &DateTime<Utc>, data: &GpsData) -> Vec<u8> {
+        let (velocity_north, velocity_east, velocity_down) = Self::velocity_ned(data);
+        let speed = data.gps_ground_speed.hypot(data.vertical_speed);
+
+        let mut payload = Vec::with_capacity(36);
+
+        payload.extend_from_slice(&Self::gps_time_of_week_ms(date).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_centimeters(velocity_north).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_centimeters(velocity_east).to_le_bytes());
+        payload.extend_from_slice(&Self::encode_centimeters(velocity_down).to_le_bytes());
+        payload.extend_from_slice(&(Self::encode_centimeters(speed) as u32).to_le_bytes());
+        payload.extend_from_slice(
+            &(Self::encode_centimeters(data.gps_ground_speed) as u32).to_le_bytes(),
+        );
+        payload.extend_from_slice(&Self::encode_heading(data.gps_ground_true_track).to_le_bytes());
+        payload.extend_from_slice(&(UBX_SPEED_ACCURACY_MM_S / 10).to_le_bytes());
+        payload.extend_from_slice(
+            &(Self::encode_heading(UBX_HEADING_ACCURACY_DEG) as u32).to_le_bytes(),
+        );
+
+        payload
+    }
+
+    fn gps_time_of_week_ms(date: &DateTime<Utc>) -> u32 {
+        (date.timestamp_millis() - GPS_EPOCH_UNIX_MS + GPS_LEAP_SECONDS_MS).rem_euclid(GPS_WEEK_MS)
+            as u32
+    }
+
+    fn velocity_ned(data: &GpsData) -> (f64, f64, f64) {
+        let track = data.gps_ground_true_track.to_radians();
+
+        (
+            data.gps_ground_speed * track.cos(),
+            data.gps_ground_speed * track.sin(),
+            -data.vertical_speed,
+        )
+    }
+
+    /// The horizontal and vertical accuracy estimates, in millimeters.
+    fn position_accuracy(constellation: &Constellation) -> (u32, u32) {
+        (
+            (constellation.hdop * UBX_USER_EQUIVALENT_RANGE_ERROR_M * 1000.0).round() as u32,
+            (constellation.vdop * UBX_USER_EQUIVALENT_RANGE_ERROR_M * 1000.0).round() as u32,
+        )
+    }
+
+    fn encode_degrees(degrees: f64, scale: f64) -> i32 {
+        (degrees * scale).round() as i32
+    }
+
+    fn encode_heading(degrees: f64) -> i32 {
+        Self::encode_degrees(degrees.rem_euclid(360.0), 1e5)
+    }
+
+    fn encode_millimeters(meters: f64) -> i32 {
+        (meters * 1000.0).round() as i32
+    }
+
+    fn encode_centimeters(meters: f64) -> i32 {
+        (meters * 100.0).round() as i32
+    }
+
+    /// Surrounds the payload with the sync characters, the class, ID and length, and the Fletcher checksum.
+    fn frame(class: u8, id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(payload.len() + 8);
+
+        frame.push(UBX_SYNC_CHAR_1);
+        frame.push(UBX_SYNC_CHAR_2);
+        frame.push(class);
+        frame.push(id);
+        frame.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        frame.extend_from_slice(payload);
+
+        let (ck_a, ck_b) = frame[2..].iter().fold((0u8, 0u8), |(ck_a, ck_b), &byte| {
+            let ck_a = ck_a.wrapping_add(byte);
+            (ck_a, ck_b.wrapping_add(ck_a))
+        });
+
+        frame.push(ck_a);
+        frame.push(ck_b);
+
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use crate::broadcaster::Constellation;
+
+    use super::Ubx;
+
+    fn read_i32(payload: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes(payload[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_frame() {
+        // the NAV-PVT poll request from the u-blox protocol specification
+        assert_eq!(
+            Ubx::frame(0x01, 0x07, &[]),
+            vec![0xB5, 0x62, 0x01, 0x07, 0x00, 0x00, 0x08, 0x19]
+        );
+    }
+
+    #[test]
+    fn test_gps_time_of_week_ms() {
+        // Sunday, the start of a GPS week
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(0, 0, 0, 0))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        assert_eq!(Ubx::gps_time_of_week_ms(&date), 18_000);
+    }
+
+    #[test]
+    fn test_nav_pvt_payload() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 90.0,
+            gps_magnetic_variation: -1.5,
+            gps_ground_speed: 50.0,
+            vertical_speed: 2.5,
+        };
+
+        let constellation = Constellation::visible_from(&date, &data);
+
+        let result = Ubx::nav_pvt_payload(&date, &data, &constellation);
+
+        assert_eq!(result.len(), 92);
+        assert_eq!(read_i32(&result, 0), 76_248_750);
+        assert_eq!(&result[4..12], &[0xE6, 0x07, 10, 30, 21, 10, 30, 0x0F]);
+        assert_eq!(result[20], 3);
+        assert_eq!(result[23] as usize, constellation.satellites.len());
+        assert_eq!(read_i32(&result, 24), -1_180_920);
+        assert_eq!(read_i32(&result, 28), 515_098_650);
+        assert_eq!(read_i32(&result, 36), 100_500);
+        assert_eq!(read_i32(&result, 48), 0);
+        assert_eq!(read_i32(&result, 52), 50_000);
+        assert_eq!(read_i32(&result, 56), -2_500);
+        assert_eq!(read_i32(&result, 60), 50_000);
+        assert_eq!(read_i32(&result, 64), 9_000_000);
+        assert_eq!(i16::from_le_bytes([result[88], result[89]]), -150);
+    }
+
+    #[test]
+    fn test_nav_velned_payload() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 180.0,
+            gps_magnetic_variation: -1.5,
+            gps_ground_speed: 30.0,
+            vertical_speed: -40.0,
+        };
+
+        let result = Ubx::nav_velned_payload(&date, &data);
+
+        assert_eq!(result.len(), 36);
+        assert_eq!(read_i32(&result, 4), -3_000);
+        assert_eq!(read_i32(&result, 8), 0);
+        assert_eq!(read_i32(&result, 12), 4_000);
+        assert_eq!(read_i32(&result, 16), 5_000);
+        assert_eq!(read_i32(&result, 20), 3_000);
+        assert_eq!(read_i32(&result, 24), 18_000_000);
+    }
+}
@@ -1,43 +1,42 @@
-use std::{io, net::UdpSocket};
+use std::{
+    io,
+    net::{
+        IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs,
+        UdpSocket,
+    },
+    time::{Duration, SystemTime},
+};
 
-use tracing::{error, instrument, trace};
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
+use tracing::{error, info, instrument, trace, warn};
 
-use crate::system::simconnect_objects::GpsData;
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
 
-use super::{BroadcasterExt, UdpConfig};
+use super::{BroadcasterExt, MulticastConfig, UdpConfig};
+
+const UDP_RESOLVE_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Debug)]
 pub struct Udp {
-    socket: UdpSocket,
-    port: u16,
-    netmask: String,
+    transport: UdpTransport,
+    attitude_interval: Option<Duration>,
+    last_attitude: Option<SystemTime>,
 }
 
 impl Udp {
     #[instrument(name = "Udp::new")]
     pub fn new(config: UdpConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
-        let local_port = config.port - 1;
-        let broadcast_netmask = config.netmask.clone();
+        let attitude_interval = config
+            .attitude_rate_hz
+            .filter(|rate| *rate > 0)
+            .map(|rate| Duration::from_secs(1) / rate);
 
-        let socket = UdpSocket::bind(format!("{}:{}", "0.0.0.0", local_port)).map_err(|e| {
-            error!(error = ?e, "failed to bind to the UDP socket");
-            e
-        })?;
-        socket.set_broadcast(true).map_err(|e| {
-            error!(error = ?e, "failed to set the UDP socket to broadcast");
-            e
-        })?;
-        socket
-            .connect((broadcast_netmask, local_port))
-            .map_err(|e| {
-                error!(error = ?e, "failed to connect to the UDP socket");
-                e
-            })?;
+        let transport = UdpTransport::new(config)?;
 
         Ok(Box::new(Udp {
-            socket,
-            port: config.port,
-            netmask: config.netmask,
+            transport,
+            attitude_interval,
+            last_attitude: None,
         }))
     }
 }
@@ -52,17 +51,505 @@ impl BroadcasterExt for Udp {
             data.lon, data.lat, data.alt, track, data.gps_ground_speed
         );
 
-        self.socket
-            .send_to(
-                message.as_bytes(),
-                format!("{}:{}", &self.netmask, self.port),
-            )
-            .map(|_| {
-                trace!("Successfully sent broadcast over UDP");
+        self.transport.send(message.as_bytes())
+    }
+
+    #[instrument(name = "Udp::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        if !self.should_send_attitude() {
+            return Ok(());
+        }
+
+        let message = Self::convert_attitude_data_to_xatt(&data);
+        self.transport.send(message.as_bytes())?;
+        self.last_attitude.replace(SystemTime::now());
+
+        Ok(())
+    }
+}
+
+impl Udp {
+    fn should_send_attitude(&self) -> bool {
+        match (self.attitude_interval, self.last_attitude) {
+            (Some(interval), Some(last_attitude)) => {
+                if let Ok(elapsed) = last_attitude.elapsed() {
+                    if elapsed >= interval {
+                        return true;
+                    }
+                }
+            }
+            (Some(_), None) => {
+                return true;
+            }
+            (None, _) => (),
+        }
+
+        false
+    }
+
+    fn convert_attitude_data_to_xatt(data: &AttitudeData) -> String {
+        format!(
+            "XATTMSFS,{:.1},{:.1},{:.1}",
+            data.true_heading,
+            data.pitch_up(),
+            data.roll_right()
+        )
+    }
+}
+
+/// The UDP sockets shared by the broadcasters that send datagrams over the network,
+/// delivering each datagram to the broadcast address, the multicast group and every unicast target.
+/// Targets that cannot be resolved are skipped and resolved again periodically.
+#[derive(Debug)]
+pub struct UdpTransport {
+    config: UdpConfig,
+    socket_v4: Option<UdpSocket>,
+    socket_v6: Option<UdpSocket>,
+    destinations: Vec<SocketAddr>,
+    unresolved: Vec<String>,
+    last_resolve: SystemTime,
+}
+
+impl UdpTransport {
+    pub fn new(config: UdpConfig) -> Result<Self, io::Error> {
+        let mut destinations = Vec::new();
+
+        if let Some(netmask) = &config.netmask {
+            destinations.push(Self::resolve(&format!("{}:{}", netmask, config.port))?);
+        }
+
+        if let Some(multicast) = &config.multicast {
+            destinations.push(Self::multicast_destination(multicast, config.port)?);
+        }
+
+        let mut unresolved = Vec::new();
+
+        for target in &config.targets {
+            match Self::resolve(target) {
+                Ok(destination) => destinations.push(destination),
+                Err(_) => {
+                    warn!(target = ?target, "skipping the UDP target until it resolves");
+                    unresolved.push(target.clone());
+                }
+            }
+        }
+
+        if destinations.is_empty() && !unresolved.is_empty() {
+            error!("none of the UDP targets could be resolved");
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "none of the UDP targets could be resolved",
+            ));
+        }
+
+        if destinations.is_empty() {
+            error!("no UDP broadcast netmask, multicast group or unicast targets configured");
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no UDP broadcast netmask, multicast group or unicast targets configured",
+            ));
+        }
+
+        let socket_v4 = destinations
+            .iter()
+            .any(SocketAddr::is_ipv4)
+            .then(|| Self::bind_v4(&config))
+            .transpose()?;
+        let socket_v6 = destinations
+            .iter()
+            .any(SocketAddr::is_ipv6)
+            .then(|| Self::bind_v6(&config))
+            .transpose()?;
+
+        Ok(UdpTransport {
+            config,
+            socket_v4,
+            socket_v6,
+            destinations,
+            unresolved,
+            last_resolve: SystemTime::now(),
+        })
+    }
+
+    /// Sends the message to every destination. A destination that fails is reported without affecting the others,
+    /// so an error is only returned when the message could not be delivered anywhere.
+    pub fn send(&mut self, message: &[u8]) -> Result<(), io::Error> {
+        if self.should_resolve() {
+            self.resolve_unresolved();
+        }
+
+        let mut delivered = false;
+        let mut last_error = None;
+
+        for destination in &self.destinations {
+            let socket = match destination {
+                SocketAddr::V4(_) => self.socket_v4.as_ref(),
+                SocketAddr::V6(_) => self.socket_v6.as_ref(),
+            }
+            .expect("a socket is bound for every address family with a destination");
+
+            match socket.send_to(message, destination) {
+                Ok(_) => {
+                    trace!(destination = ?destination, "Successfully sent over UDP");
+                    delivered = true;
+                }
+                Err(e) => {
+                    warn!(error = ?e, destination = ?destination, "failed to send over UDP");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !delivered => {
+                error!(error = ?e, "failed to broadcast over UDP");
+                Err(e)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn should_resolve(&self) -> bool {
+        !self.unresolved.is_empty()
+            && self
+                .last_resolve
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed >= UDP_RESOLVE_INTERVAL)
+    }
+
+    fn resolve_unresolved(&mut self) {
+        self.last_resolve = SystemTime::now();
+
+        let unresolved = std::mem::take(&mut self.unresolved);
+        for target in unresolved {
+            let bound = match Self::resolve(&target) {
+                Ok(destination @ SocketAddr::V4(_)) if self.socket_v4.is_none() => {
+                    Self::bind_v4(&self.config).map(|socket| {
+                        self.socket_v4.replace(socket);
+                        destination
+                    })
+                }
+                Ok(destination @ SocketAddr::V6(_)) if self.socket_v6.is_none() => {
+                    Self::bind_v6(&self.config).map(|socket| {
+                        self.socket_v6.replace(socket);
+                        destination
+                    })
+                }
+                result => result,
+            };
+
+            match bound {
+                Ok(destination) => {
+                    info!(target = ?target, destination = ?destination, "UDP target resolved");
+                    self.destinations.push(destination);
+                }
+                Err(_) => self.unresolved.push(target),
+            }
+        }
+    }
+
+    fn bind_v4(config: &UdpConfig) -> Result<UdpSocket, io::Error> {
+        // an ephemeral port, so that broadcasters sending to the same port do not collide
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|e| {
+            error!(error = ?e, "failed to bind to the UDP socket");
+            e
+        })?;
+
+        if config.netmask.is_some() {
+            socket.set_broadcast(true).map_err(|e| {
+                error!(error = ?e, "failed to set the UDP socket to broadcast");
+                e
+            })?;
+        }
+
+        if let Some(multicast) = config.multicast.as_ref().filter(|m| m.group.is_ipv4()) {
+            if let Some(ttl) = multicast.ttl {
+                socket.set_multicast_ttl_v4(ttl).map_err(|e| {
+                    error!(error = ?e, "failed to set the UDP multicast TTL");
+                    e
+                })?;
+            }
+
+            if let Some(interface) = &multicast.interface {
+                let interface = interface.parse::<Ipv4Addr>().map_err(|e| {
+                    error!(error = ?e, "the IPv4 multicast interface must be an IPv4 address");
+                    io::Error::new(io::ErrorKind::InvalidInput, e)
+                })?;
+
+                SockRef::from(&socket)
+                    .set_multicast_if_v4(&interface)
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to set the UDP multicast interface");
+                        e
+                    })?;
+            }
+        }
+
+        Ok(socket)
+    }
+
+    fn bind_v6(config: &UdpConfig) -> Result<UdpSocket, io::Error> {
+        let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP)).map_err(|e| {
+            error!(error = ?e, "failed to create the IPv6 UDP socket");
+            e
+        })?;
+        // the IPv4 destinations have their own socket
+        socket.set_only_v6(true)?;
+        socket
+            .bind(&SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)).into())
+            .map_err(|e| {
+                error!(error = ?e, "failed to bind to the IPv6 UDP socket");
+                e
+            })?;
+
+        if let Some(multicast) = config.multicast.as_ref().filter(|m| m.group.is_ipv6()) {
+            if let Some(ttl) = multicast.ttl {
+                socket.set_multicast_hops_v6(ttl).map_err(|e| {
+                    error!(error = ?e, "failed to set the UDP multicast hop limit");
+                    e
+                })?;
+            }
+
+            if multicast.interface.is_some() {
+                socket
+                    .set_multicast_if_v6(Self::interface_index(multicast)?)
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to set the UDP multicast interface");
+                        e
+                    })?;
+            }
+        }
+
+        Ok(socket.into())
+    }
+
+    fn multicast_destination(
+        multicast: &MulticastConfig,
+        port: u16,
+    ) -> Result<SocketAddr, io::Error> {
+        match multicast.group {
+            IpAddr::V4(group) if group.is_multicast() => Ok(SocketAddrV4::new(group, port).into()),
+            // link-local groups can only be reached through the interface in the scope id
+            IpAddr::V6(group) if group.is_multicast() => {
+                Ok(SocketAddrV6::new(group, port, 0, Self::interface_index(multicast)?).into())
+            }
+            group => {
+                error!(group = ?group, "the UDP multicast group is not a multicast address");
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the UDP multicast group is not a multicast address",
+                ))
+            }
+        }
+    }
+
+    /// IPv6 identifies the outgoing interface by its index, with 0 letting the system choose.
+    fn interface_index(multicast: &MulticastConfig) -> Result<u32, io::Error> {
+        multicast
+            .interface
+            .as_deref()
+            .map_or(Ok(0), str::parse)
+            .map_err(|e| {
+                error!(error = ?e, "the IPv6 multicast interface must be an interface index");
+                io::Error::new(io::ErrorKind::InvalidInput, e)
+            })
+    }
+
+    fn resolve(target: &str) -> Result<SocketAddr, io::Error> {
+        target
+            .to_socket_addrs()
+            .and_then(|mut addrs| {
+                addrs.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "no address found for the target")
+                })
             })
             .map_err(|e| {
-                error!(error = ?e, "failed to broadcast over UDP");
+                error!(error = ?e, target = ?target, "failed to resolve the UDP target");
                 e
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, UdpSocket};
+
+    use crate::broadcaster::{MulticastConfig, UdpConfig};
+
+    use super::{Udp, UdpTransport};
+
+    #[test]
+    fn test_send_to_unicast_targets() {
+        let first = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let second = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let config = UdpConfig {
+            port: 0,
+            netmask: None,
+            targets: vec![
+                first.local_addr().unwrap().to_string(),
+                second.local_addr().unwrap().to_string(),
+            ],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        let mut transport = UdpTransport::new(config).unwrap();
+        transport.send(b"XGPSMSFS").unwrap();
+
+        let mut buffer = [0u8; 16];
+        for socket in [first, second] {
+            let len = socket.recv(&mut buffer).unwrap();
+            assert_eq!(&buffer[..len], b"XGPSMSFS");
+        }
+    }
+
+    #[test]
+    fn test_send_to_ipv4_and_ipv6_targets() {
+        let v4 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let v6 = UdpSocket::bind("[::1]:0").unwrap();
+
+        let config = UdpConfig {
+            port: 0,
+            netmask: None,
+            targets: vec![
+                v4.local_addr().unwrap().to_string(),
+                v6.local_addr().unwrap().to_string(),
+            ],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        let mut transport = UdpTransport::new(config).unwrap();
+        transport.send(b"XGPSMSFS").unwrap();
+
+        let mut buffer = [0u8; 16];
+        for socket in [v4, v6] {
+            let len = socket.recv(&mut buffer).unwrap();
+            assert_eq!(&buffer[..len], b"XGPSMSFS");
+        }
+    }
+
+    #[test]
+    fn test_new_twice_for_the_same_port() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = receiver.local_addr().unwrap();
+
+        let config = UdpConfig {
+            port: addr.port(),
+            netmask: None,
+            targets: vec![addr.to_string()],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        let first = UdpTransport::new(config.clone()).unwrap();
+        let second = UdpTransport::new(config).unwrap();
+
+        assert_ne!(
+            first.socket_v4.unwrap().local_addr().unwrap(),
+            second.socket_v4.unwrap().local_addr().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_multicast_destination() {
+        let multicast = MulticastConfig {
+            group: "239.255.12.34".parse().unwrap(),
+            ttl: Some(4),
+            interface: Some("192.168.1.10".to_string()),
+        };
+
+        let result = UdpTransport::multicast_destination(&multicast, 49002).unwrap();
+
+        assert_eq!(result, "239.255.12.34:49002".parse::<SocketAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_multicast_destination_ipv6_link_local() {
+        let multicast = MulticastConfig {
+            group: "ff02::1".parse().unwrap(),
+            ttl: None,
+            interface: Some("3".to_string()),
+        };
+
+        let result = UdpTransport::multicast_destination(&multicast, 49002).unwrap();
+
+        assert_eq!(result, "[ff02::1%3]:49002".parse::<SocketAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_multicast_destination_not_multicast() {
+        let multicast = MulticastConfig {
+            group: "192.168.1.255".parse().unwrap(),
+            ttl: None,
+            interface: None,
+        };
+
+        assert!(UdpTransport::multicast_destination(&multicast, 49002).is_err());
+    }
+
+    #[test]
+    fn test_new_with_an_unresolvable_target() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let config = UdpConfig {
+            port: 0,
+            netmask: None,
+            targets: vec![
+                "unresolvable.invalid:49002".to_string(),
+                receiver.local_addr().unwrap().to_string(),
+            ],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        let mut transport = UdpTransport::new(config).unwrap();
+        assert_eq!(transport.unresolved, vec!["unresolvable.invalid:49002"]);
+
+        transport.send(b"XGPSMSFS").unwrap();
+
+        let mut buffer = [0u8; 16];
+        let len = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"XGPSMSFS");
+    }
+
+    #[test]
+    fn test_new_without_resolvable_targets() {
+        let config = UdpConfig {
+            port: 0,
+            netmask: None,
+            targets: vec!["unresolvable.invalid:49002".to_string()],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        assert!(UdpTransport::new(config).is_err());
+    }
+
+    #[test]
+    fn test_new_without_destinations() {
+        let config = UdpConfig {
+            port: 0,
+            netmask: None,
+            targets: vec![],
+            multicast: None,
+            attitude_rate_hz: None,
+        };
+
+        assert!(UdpTransport::new(config).is_err());
+    }
+
+    #[test]
+    fn test_convert_attitude_data_to_xatt() {
+        let data = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 310.55,
+            pitch: -5.25,
+            bank: 15.0,
+        };
+
+        let result = Udp::convert_attitude_data_to_xatt(&data);
+
+        assert_eq!(result, "XATTMSFS,310.6,5.2,-15.0");
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use serde::Serialize;
+use tracing::{error, info, instrument, trace, warn};
+use tungstenite::{
+    handshake::{
+        server::{NoCallback, ServerHandshake},
+        MidHandshake,
+    },
+    protocol::WebSocketConfig as ProtocolConfig,
+    HandshakeError, Message, WebSocket as Connection,
+};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{BroadcasterExt, WebSocketConfig};
+
+/// Bumped on every breaking change to the JSON frames.
+pub const TELEMETRY_SCHEMA_VERSION: u32 = 1;
+
+// clients that fall this many frames behind are disconnected
+const WEBSOCKET_MAX_SEND_QUEUE: usize = 64;
+
+/// The versioned JSON telemetry frame, e.g. `{"version":1,"type":"gps","data":{"lat":51.5,...}}`.
+#[derive(Debug, Serialize)]
+pub struct TelemetryFrame<'a> {
+    version: u32,
+    #[serde(flatten)]
+    telemetry: Telemetry<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum Telemetry<'a> {
+    Gps(&'a GpsData),
+    Attitude(Attitude),
+}
+
+/// The attitude with conventional signs, unlike the inverted ones of SimConnect.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attitude {
+    true_heading: f64,
+    // positive with the nose up
+    pitch_up: f64,
+    // positive with the right wing down
+    roll_right: f64,
+}
+
+impl From<&AttitudeData> for Attitude {
+    fn from(data: &AttitudeData) -> Self {
+        Self {
+            true_heading: data.true_heading,
+            pitch_up: data.pitch_up(),
+            roll_right: data.roll_right(),
+        }
+    }
+}
+
+impl<'a> TelemetryFrame<'a> {
+    pub fn new(telemetry: Telemetry<'a>) -> Self {
+        Self {
+            version: TELEMETRY_SCHEMA_VERSION,
+            telemetry,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocket {
+    listener: TcpListener,
+    clients: Vec<WebSocketClient>,
+}
+
+#[derive(Debug)]
+enum WebSocketClient {
+    Handshaking(
+        SocketAddr,
+        MidHandshake<ServerHandshake<TcpStream, NoCallback>>,
+    ),
+    Open(SocketAddr, Connection<TcpStream>),
+}
+
+impl WebSocket {
+    #[instrument(name = "WebSocket::new")]
+    pub fn new(config: WebSocketConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        Ok(Box::new(Self::bind(config)?))
+    }
+
+    fn bind(config: WebSocketConfig) -> Result<Self, io::Error> {
+        let listener = TcpListener::bind((config.bind.as_str(), config.port)).map_err(|e| {
+            error!(error = ?e, "failed to bind the WebSocket listener");
+            e
+        })?;
+        listener.set_nonblocking(true).map_err(|e| {
+            error!(error = ?e, "failed to set the WebSocket listener to non-blocking");
+            e
+        })?;
+
+        Ok(WebSocket {
+            listener,
+            clients: Vec::new(),
+        })
+    }
+}
+
+impl BroadcasterExt for WebSocket {
+    #[instrument(name = "WebSocket::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        self.accept_clients();
+        self.broadcast(Telemetry::Gps(&data));
+
+        trace!("Successfully sent telemetry over WebSocket");
+
+        Ok(())
+    }
+
+    #[instrument(name = "WebSocket::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        self.broadcast(Telemetry::Attitude(Attitude::from(&data)));
+
+        Ok(())
+    }
+}
+
+impl WebSocket {
+    fn accept_clients(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    // the handshake is driven along with the broadcasts, so it never blocks the broadcaster
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        warn!(error = ?e, client = ?addr, "failed to set the WebSocket client to non-blocking");
+                        continue;
+                    }
+
+                    let config = ProtocolConfig {
+                        max_send_queue: Some(WEBSOCKET_MAX_SEND_QUEUE),
+                        ..Default::default()
+                    };
+
+                    let client = Self::handshake(
+                        addr,
+                        tungstenite::accept_with_config(stream, Some(config)),
+                    );
+                    self.clients.extend(client);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!(error = ?e, "failed to accept a WebSocket client");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handshake(
+        addr: SocketAddr,
+        result: Result<
+            Connection<TcpStream>,
+            HandshakeError<ServerHandshake<TcpStream, NoCallback>>,
+        >,
+    ) -> Option<WebSocketClient> {
+        match result {
+            Ok(connection) => {
+                info!(client = ?addr, "WebSocket client connected");
+                Some(WebSocketClient::Open(addr, connection))
+            }
+            Err(HandshakeError::Interrupted(handshake)) => {
+                Some(WebSocketClient::Handshaking(addr, handshake))
+            }
+            Err(HandshakeError::Failure(e)) => {
+                warn!(error = ?e, client = ?addr, "WebSocket handshake failed");
+                None
+            }
+        }
+    }
+
+    fn broadcast(&mut self, telemetry: Telemetry) {
+        let frame = match serde_json::to_string(&TelemetryFrame::new(telemetry)) {
+            Ok(frame) => frame,
+            Err(e) => {
+                error!(error = ?e, "failed to serialize the telemetry frame");
+                return;
+            }
+        };
+
+        self.clients = self
+            .clients
+            .drain(..)
+            .filter_map(|client| match client {
+                WebSocketClient::Handshaking(addr, handshake) => {
+                    Self::handshake(addr, handshake.handshake())
+                }
+                WebSocketClient::Open(addr, mut connection) => {
+                    Self::write(addr, &mut connection, &frame)
+                        .then_some(WebSocketClient::Open(addr, connection))
+                }
+            })
+            .collect();
+    }
+
+    /// Returns `false` once the client has disconnected.
+    fn write(addr: SocketAddr, connection: &mut Connection<TcpStream>, frame: &str) -> bool {
+        // the incoming frames are only read to answer pings and notice closes
+        loop {
+            match connection.read_message() {
+                Ok(Message::Close(_)) => {
+                    info!(client = ?addr, "WebSocket client disconnected");
+                    return false;
+                }
+                Ok(_) => (),
+                Err(tungstenite::Error::Io(ref e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                    break
+                }
+                Err(e) => {
+                    info!(error = ?e, client = ?addr, "WebSocket client disconnected");
+                    return false;
+                }
+            }
+        }
+
+        match connection.write_message(Message::Text(frame.to_string())) {
+            Ok(_) => true,
+            // the frame is queued and sent along with the next one
+            Err(tungstenite::Error::Io(ref e)) if e.kind() == io::ErrorKind::WouldBlock => true,
+            Err(tungstenite::Error::SendQueueFull(_)) => {
+                warn!(client = ?addr, "WebSocket client is not keeping up");
+                false
+            }
+            Err(e) => {
+                info!(error = ?e, client = ?addr, "WebSocket client disconnected");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpStream, thread};
+
+    use tungstenite::Message;
+
+    use crate::broadcaster::{BroadcasterExt, WebSocketConfig};
+
+    use super::{Attitude, Telemetry, TelemetryFrame, WebSocket};
+
+    #[test]
+    fn test_telemetry_frame() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.5,
+            vertical_speed: 0.0,
+        };
+
+        let result = serde_json::to_string(&TelemetryFrame::new(Telemetry::Gps(&data))).unwrap();
+
+        assert_eq!(
+            result,
+            "{\"version\":1,\"type\":\"gps\",\"data\":{\"lat\":51.509865,\"lon\":-0.118092,\"alt\":0.0,\"pressureAltitude\":3.0,\"gpsGroundTrueTrack\":310.55,\"gpsMagneticVariation\":5.0,\"gpsGroundSpeed\":100.5,\"verticalSpeed\":0.0}}"
+        );
+    }
+
+    #[test]
+    fn test_attitude_telemetry_frame() {
+        let data = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 310.55,
+            pitch: -5.25,
+            bank: 15.0,
+        };
+
+        let result = serde_json::to_string(&TelemetryFrame::new(Telemetry::Attitude(
+            Attitude::from(&data),
+        )))
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "{\"version\":1,\"type\":\"attitude\",\"data\":{\"trueHeading\":310.55,\"pitchUp\":5.25,\"rollRight\":-15.0}}"
+        );
+    }
+
+    #[test]
+    fn test_send_to_connected_clients() {
+        let config = WebSocketConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+        };
+
+        let mut server = WebSocket::bind(config).unwrap();
+        let addr = server.listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            let (mut socket, _) = tungstenite::client(format!("ws://{addr}/"), stream).unwrap();
+
+            socket.read_message().unwrap()
+        });
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 0.0,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.5,
+            vertical_speed: 0.0,
+        };
+
+        // keep sending while the client connects and completes the handshake
+        while !client.is_finished() {
+            server.send(data.clone()).unwrap();
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        match client.join().unwrap() {
+            Message::Text(frame) => assert!(frame.starts_with("{\"version\":1,\"type\":\"gps\"")),
+            message => panic!("unexpected message {message:?}"),
+        }
+    }
+}
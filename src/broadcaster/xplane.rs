@@ -0,0 +1,284 @@
+use std::io;
+
+use tracing::{instrument, trace};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{BroadcasterExt, UdpTransport, XPlaneDataConfig, XPlanePacket};
+
+// X-Plane fills the fifth byte of the header with internal data, which the receivers ignore
+const XPLANE_DATA_HEADER: &[u8; 5] = b"DATA*";
+const XPLANE_RPOS_HEADER: &[u8; 5] = b"RPOS4";
+
+const XPLANE_DATA_INDEX_SPEEDS: i32 = 3;
+const XPLANE_DATA_INDEX_PITCH_ROLL_HEADINGS: i32 = 17;
+const XPLANE_DATA_INDEX_LAT_LON_ALT: i32 = 20;
+
+// the value X-Plane sends for the fields of a DATA record it has nothing to report in
+const XPLANE_NO_DATA: f32 = -999.0;
+
+const FEET_PER_METER: f64 = 3.28084;
+const MPH_PER_METER_PER_SECOND: f64 = 2.2369363;
+
+/// The binary `DATA` and `RPOS` packets X-Plane sends over UDP, for apps that only support X-Plane natively.
+#[derive(Debug)]
+pub struct XPlaneData {
+    transport: UdpTransport,
+    packets: Vec<XPlanePacket>,
+    // the attitude arrives separately and is sent along with the next position
+    attitude: Option<AttitudeData>,
+}
+
+impl XPlaneData {
+    #[instrument(name = "XPlaneData::new")]
+    pub fn new(config: XPlaneDataConfig) -> Result<Box<dyn BroadcasterExt>, io::Error> {
+        let transport = UdpTransport::new(config.udp)?;
+
+        Ok(Box::new(XPlaneData {
+            transport,
+            packets: config.packets,
+            attitude: None,
+        }))
+    }
+}
+
+impl BroadcasterExt for XPlaneData {
+    #[instrument(name = "XPlaneData::send", skip(self, data))]
+    fn send(&mut self, data: GpsData) -> Result<(), io::Error> {
+        for packet in &self.packets {
+            let message = match packet {
+                XPlanePacket::Data => Self::data_message(&data, self.attitude.as_ref()),
+                XPlanePacket::Rpos => Self::rpos_message(&data, self.attitude.as_ref()),
+            };
+
+            self.transport.send(&message)?;
+        }
+
+        trace!("Successfully sent X-Plane packets over UDP");
+
+        Ok(())
+    }
+
+    #[instrument(name = "XPlaneData::send_attitude", skip(self, data))]
+    fn send_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        self.attitude.replace(data);
+
+        Ok(())
+    }
+}
+
+impl XPlaneData {
+    fn data_message(data: &GpsData, attitude: Option<&AttitudeData>) -> Vec<u8> {
+        let ground_speed_knots = data.gps_ground_speed_in_knots() as f32;
+        let ground_speed_mph = (data.gps_ground_speed * MPH_PER_METER_PER_SECOND) as f32;
+
+        let mut message = Vec::with_capacity(XPLANE_DATA_HEADER.len() + 3 * 36);
+        message.extend_from_slice(XPLANE_DATA_HEADER);
+
+        // only the ground speed is known, the air speeds are left empty
+        Self::push_data_record(
+            &mut message,
+            XPLANE_DATA_INDEX_SPEEDS,
+            [
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+                ground_speed_knots,
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+                ground_speed_mph,
+            ],
+        );
+
+        if let Some(attitude) = attitude {
+            let magnetic_heading =
+                (attitude.true_heading - data.gps_magnetic_variation).rem_euclid(360.0);
+
+            Self::push_data_record(
+                &mut message,
+                XPLANE_DATA_INDEX_PITCH_ROLL_HEADINGS,
+                [
+                    attitude.pitch_up() as f32,
+                    attitude.roll_right() as f32,
+                    attitude.true_heading as f32,
+                    magnetic_heading as f32,
+                    XPLANE_NO_DATA,
+                    XPLANE_NO_DATA,
+                    XPLANE_NO_DATA,
+                    XPLANE_NO_DATA,
+                ],
+            );
+        }
+
+        Self::push_data_record(
+            &mut message,
+            XPLANE_DATA_INDEX_LAT_LON_ALT,
+            [
+                data.lat as f32,
+                data.lon as f32,
+                (data.alt * FEET_PER_METER) as f32,
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+                (data.pressure_altitude * FEET_PER_METER) as f32,
+                XPLANE_NO_DATA,
+                XPLANE_NO_DATA,
+            ],
+        );
+
+        message
+    }
+
+    /// A record is the index followed by eight values, all little-endian.
+    fn push_data_record(message: &mut Vec<u8>, index: i32, values: [f32; 8]) {
+        message.extend_from_slice(&index.to_le_bytes());
+
+        for value in values {
+            message.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn rpos_message(data: &GpsData, attitude: Option<&AttitudeData>) -> Vec<u8> {
+        let (pitch, heading, roll) = attitude
+            .map(|a| (a.pitch_up(), a.true_heading, a.roll_right()))
+            .unwrap_or((0.0, data.gps_ground_true_track, 0.0));
+
+        // X-Plane uses OpenGL coordinates, where x points east, y up and z south
+        let track = data.gps_ground_true_track.to_radians();
+        let velocity_east = data.gps_ground_speed * track.sin();
+        let velocity_south = -data.gps_ground_speed * track.cos();
+
+        let mut message = Vec::with_capacity(XPLANE_RPOS_HEADER.len() + 64);
+        message.extend_from_slice(XPLANE_RPOS_HEADER);
+
+        for value in [data.lon, data.lat, data.alt] {
+            message.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // the height above ground and the rotation rates are not known
+        for value in [
+            0.0,
+            pitch,
+            heading,
+            roll,
+            velocity_east,
+            data.vertical_speed,
+            velocity_south,
+            0.0,
+            0.0,
+            0.0,
+        ] {
+            message.extend_from_slice(&(value as f32).to_le_bytes());
+        }
+
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XPlaneData;
+
+    fn read_f32(message: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes(message[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_f64(message: &[u8], offset: usize) -> f64 {
+        f64::from_le_bytes(message[offset..offset + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_data_message() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 950.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let attitude = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 2.5,
+            pitch: -5.25,
+            bank: 15.0,
+        };
+
+        let result = XPlaneData::data_message(&data, Some(&attitude));
+
+        assert_eq!(result.len(), 5 + 3 * 36);
+        assert_eq!(&result[..5], b"DATA*");
+
+        assert_eq!(i32::from_le_bytes(result[5..9].try_into().unwrap()), 3);
+        assert_eq!(read_f32(&result, 9), -999.0);
+        assert_eq!(read_f32(&result, 21), 195.35637);
+        assert_eq!(read_f32(&result, 37), 224.8121);
+
+        assert_eq!(i32::from_le_bytes(result[41..45].try_into().unwrap()), 17);
+        assert_eq!(read_f32(&result, 45), 5.25);
+        assert_eq!(read_f32(&result, 49), -15.0);
+        assert_eq!(read_f32(&result, 53), 2.5);
+        assert_eq!(read_f32(&result, 57), 357.5);
+
+        assert_eq!(i32::from_le_bytes(result[77..81].try_into().unwrap()), 20);
+        assert_eq!(read_f32(&result, 81), 51.509865);
+        assert_eq!(read_f32(&result, 85), -0.118092);
+        assert_eq!(read_f32(&result, 89), 3280.84);
+        assert_eq!(read_f32(&result, 101), 3116.798);
+    }
+
+    #[test]
+    fn test_data_message_without_attitude() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 950.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = XPlaneData::data_message(&data, None);
+
+        assert_eq!(result.len(), 5 + 2 * 36);
+        assert_eq!(i32::from_le_bytes(result[41..45].try_into().unwrap()), 20);
+    }
+
+    #[test]
+    fn test_rpos_message() {
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            pressure_altitude: 950.0,
+            gps_ground_true_track: 90.0,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.0,
+            vertical_speed: 2.5,
+        };
+
+        let attitude = crate::system::simconnect_objects::AttitudeData {
+            true_heading: 92.5,
+            pitch: -5.25,
+            bank: 15.0,
+        };
+
+        let result = XPlaneData::rpos_message(&data, Some(&attitude));
+
+        assert_eq!(result.len(), 69);
+        assert_eq!(&result[..5], b"RPOS4");
+        assert_eq!(read_f64(&result, 5), -0.118092);
+        assert_eq!(read_f64(&result, 13), 51.509865);
+        assert_eq!(read_f64(&result, 21), 1000.0);
+        assert_eq!(read_f32(&result, 33), 5.25);
+        assert_eq!(read_f32(&result, 37), 92.5);
+        assert_eq!(read_f32(&result, 41), -15.0);
+        assert_eq!(read_f32(&result, 45), 100.0);
+        assert_eq!(read_f32(&result, 49), 2.5);
+        assert!(read_f32(&result, 53).abs() < 1e-3);
+    }
+}
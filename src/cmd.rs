@@ -1,5 +1,5 @@
 use opentelemetry_api::Context;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{fmt, time};
 use tokio::sync::oneshot::Receiver;
 use tokio::sync::{self, mpsc::Sender, oneshot::error::TryRecvError};
@@ -9,7 +9,9 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::{
     broadcaster::BroadcasterConfig,
-    system::messages::{CoordinatorMessage, GetStatusMessage, RefreshRate},
+    input::SourceConfig,
+    recorder::RecorderConfig,
+    system::messages::{BroadcasterStatus, CoordinatorMessage, GetStatusMessage, RefreshRate},
 };
 
 pub struct AppState {
@@ -20,7 +22,33 @@ pub struct AppState {
 #[serde(rename_all = "camelCase")]
 pub struct StartOptions {
     pub refresh_rate: RefreshRate,
-    pub config: BroadcasterConfig,
+    // the simulator unless another source is selected, which is required without SimConnect
+    #[cfg_attr(windows, serde(default))]
+    pub source: SourceConfig,
+    // a single config is still accepted for backwards compatibility
+    #[serde(alias = "config", deserialize_with = "deserialize_broadcaster_configs")]
+    pub configs: Vec<BroadcasterConfig>,
+    #[serde(default)]
+    pub recorders: Vec<RecorderConfig>,
+}
+
+fn deserialize_broadcaster_configs<'de, D>(
+    deserializer: D,
+) -> Result<Vec<BroadcasterConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(BroadcasterConfig),
+        Many(Vec<BroadcasterConfig>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(config) => vec![config],
+        OneOrMany::Many(configs) => configs,
+    })
 }
 
 #[derive(Debug, Serialize)]
@@ -62,32 +90,6 @@ where
 const RESPONSE_CHANNEL_RETRIES: u64 = 10;
 const RESPONSE_CHANNEL_RETRY_DELAY_MS: time::Duration = time::Duration::from_millis(100);
 
-// The commands definitions
-// Deserialized from JS
-#[derive(Debug, Deserialize)]
-#[serde(tag = "cmd", rename_all = "camelCase")]
-pub enum Cmd {
-    #[serde(rename_all = "camelCase")]
-    Start {
-        request_id: String,
-        options: StartOptions,
-        callback: String,
-        error: String,
-    },
-    #[serde(rename_all = "camelCase")]
-    Stop {
-        request_id: String,
-        callback: String,
-        error: String,
-    },
-    #[serde(rename_all = "camelCase")]
-    Status {
-        request_id: String,
-        callback: String,
-        error: String,
-    },
-}
-
 #[instrument(name = "cmd::cmd_get_available_com_ports", skip(state))]
 #[tauri::command]
 pub async fn cmd_get_available_com_ports(
@@ -129,7 +131,9 @@ pub async fn cmd_start(
         .send(CoordinatorMessage::Start {
             context: Span::current().context(),
             refresh_rate: options.refresh_rate,
-            config: options.config,
+            source: options.source,
+            configs: options.configs,
+            recorders: options.recorders,
         })
         .await;
 
@@ -200,6 +204,119 @@ pub async fn cmd_get_status(
     }
 }
 
+#[instrument(name = "cmd::cmd_get_broadcasters_status", skip(state))]
+#[tauri::command]
+pub async fn cmd_get_broadcasters_status(
+    request_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CommandResponse<Vec<BroadcasterStatus>>, CommandError> {
+    let tx_local = state.tx.clone();
+    let (response_tx, response_rx) =
+        sync::oneshot::channel::<ChannelResponse<Vec<BroadcasterStatus>>>();
+    let result = tx_local
+        .send(CoordinatorMessage::BroadcastersStatus {
+            context: Span::current().context(),
+            response_channel: response_tx,
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            poll_channel_response(response_rx)
+                .instrument(info_span!("cmd::cmd_get_broadcasters_status::recv"))
+                .await
+        }
+        Err(e) => {
+            error!(error = ?e, "the mpsc channel has closed");
+            Err(CommandError::new("ERROR".to_string()))
+        }
+    }
+}
+
+#[instrument(name = "cmd::cmd_pause_replay", skip(state))]
+#[tauri::command]
+pub async fn cmd_pause_replay(
+    request_id: String,
+    paused: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<CommandResponse<bool>, CommandError> {
+    let tx_local = state.tx.clone();
+    let result = tx_local
+        .send(CoordinatorMessage::PauseReplay {
+            context: Span::current().context(),
+            paused,
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            let response = CommandResponse { data: true };
+            info!(response = ?response, "Returning");
+            Ok(response)
+        }
+        Err(e) => {
+            error!(error = ?e, "the mpsc channel has closed");
+            Err(CommandError::new("the mpsc channel has closed".to_string()))
+        }
+    }
+}
+
+#[instrument(name = "cmd::cmd_seek_replay", skip(state))]
+#[tauri::command]
+pub async fn cmd_seek_replay(
+    request_id: String,
+    position_ms: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<CommandResponse<bool>, CommandError> {
+    let tx_local = state.tx.clone();
+    let result = tx_local
+        .send(CoordinatorMessage::SeekReplay {
+            context: Span::current().context(),
+            position: time::Duration::from_millis(position_ms),
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            let response = CommandResponse { data: true };
+            info!(response = ?response, "Returning");
+            Ok(response)
+        }
+        Err(e) => {
+            error!(error = ?e, "the mpsc channel has closed");
+            Err(CommandError::new("the mpsc channel has closed".to_string()))
+        }
+    }
+}
+
+#[instrument(name = "cmd::cmd_set_replay_speed", skip(state))]
+#[tauri::command]
+pub async fn cmd_set_replay_speed(
+    request_id: String,
+    speed: f64,
+    state: tauri::State<'_, AppState>,
+) -> Result<CommandResponse<bool>, CommandError> {
+    let tx_local = state.tx.clone();
+    let result = tx_local
+        .send(CoordinatorMessage::SetReplaySpeed {
+            context: Span::current().context(),
+            speed,
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            let response = CommandResponse { data: true };
+            info!(response = ?response, "Returning");
+            Ok(response)
+        }
+        Err(e) => {
+            error!(error = ?e, "the mpsc channel has closed");
+            Err(CommandError::new("the mpsc channel has closed".to_string()))
+        }
+    }
+}
+
 #[instrument(name = "cmd::poll_channel_response", skip(rx))]
 async fn poll_channel_response<T>(
     mut rx: Receiver<ChannelResponse<T>>,
@@ -214,6 +331,8 @@ where
 
         match response {
             Ok(value) => {
+                Span::current().set_parent(value.context);
+
                 let response = CommandResponse { data: value.data };
                 debug!(response = ?response, "Returning");
                 return Ok(response);
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(windows, derive(Default))]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum SourceConfig {
+    // SimConnect is only available on Windows, where the simulator runs
+    #[cfg(windows)]
+    #[default]
+    SimConnect,
+    Replay(ReplaySourceConfig),
+    Nmea(NmeaSourceConfig),
+    Synthetic(SyntheticSourceConfig),
+}
+
+impl SourceConfig {
+    /// The `type` tag of the config.
+    pub fn source_type(&self) -> &'static str {
+        match self {
+            #[cfg(windows)]
+            SourceConfig::SimConnect => "simConnect",
+            SourceConfig::Replay(_) => "replay",
+            SourceConfig::Nmea(_) => "nmea",
+            SourceConfig::Synthetic(_) => "synthetic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaySourceConfig {
+    // a telemetry recording (.jsonl or .csv), a GPX track (.gpx) or an NMEA capture (.nmea, .log or .txt)
+    pub path: String,
+    #[serde(default = "default_replay_source_speed")]
+    pub speed: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaSourceConfig {
+    pub input: NmeaInputConfig,
+}
+
+/// A circle flown at a constant altitude and speed around a center, to test the broadcasters
+/// and the apps connected to them without the simulator.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntheticSourceConfig {
+    pub lat: f64,
+    pub lon: f64,
+    // meters
+    #[serde(default = "default_synthetic_source_alt")]
+    pub alt: f64,
+    // meters per second
+    #[serde(default = "default_synthetic_source_speed")]
+    pub speed: f64,
+    // meters
+    #[serde(default = "default_synthetic_source_radius")]
+    pub radius: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum NmeaInputConfig {
+    Serial(NmeaSerialInputConfig),
+    Tcp(NmeaTcpInputConfig),
+    Udp(NmeaUdpInputConfig),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaSerialInputConfig {
+    pub port: String,
+    #[serde(default = "default_nmea_input_baud_rate")]
+    pub baud_rate: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaTcpInputConfig {
+    // e.g. the NMEA server of a GPS receiver or of another app
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaUdpInputConfig {
+    #[serde(default = "default_nmea_input_bind")]
+    pub bind: String,
+    pub port: u16,
+}
+
+fn default_nmea_input_baud_rate() -> u32 {
+    4800
+}
+
+fn default_nmea_input_bind() -> String {
+    "0.0.0.0".to_string()
+}
+
+fn default_replay_source_speed() -> f64 {
+    1.0
+}
+
+fn default_synthetic_source_alt() -> f64 {
+    1000.0
+}
+
+fn default_synthetic_source_speed() -> f64 {
+    60.0
+}
+
+fn default_synthetic_source_radius() -> f64 {
+    2000.0
+}
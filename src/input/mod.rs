@@ -0,0 +1,19 @@
+pub mod config;
+pub mod nmea;
+pub mod nmea_source;
+pub mod replay_source;
+#[cfg(windows)]
+pub mod simconnect_source;
+pub mod source;
+pub mod stream;
+pub mod synthetic_source;
+
+pub use config::*;
+pub use nmea::*;
+pub use nmea_source::*;
+pub use replay_source::*;
+#[cfg(windows)]
+pub use simconnect_source::*;
+pub use source::*;
+pub use stream::*;
+pub use synthetic_source::*;
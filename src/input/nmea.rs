@@ -0,0 +1,348 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+
+use crate::system::simconnect_objects::GpsData;
+
+const NMEA_GGA: u8 = 0b01;
+const NMEA_RMC: u8 = 0b10;
+const KNOTS_PER_METER_PER_SECOND: f64 = 1.9438444924574;
+const FEET_PER_METER: f64 = 3.28084;
+
+/// Turns the GGA, RMC and VTG sentences of a GPS receiver, and the Garmin PGRMZ pressure altitude,
+/// back into positions. The sentences of a fix share its time, so a fix is complete once it has the
+/// same sentences as the previous one, or when the next one starts.
+#[derive(Debug)]
+pub struct NmeaParser {
+    // only RMC has the date, so a log without it starts on the Unix epoch
+    date: NaiveDate,
+    fix: NmeaFix,
+    // the timed sentences of the last fix, which every fix is expected to have
+    expected_sentences: u8,
+    last_fix: Option<(DateTime<Utc>, f64)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct NmeaFix {
+    time: Option<NaiveTime>,
+    sentences: u8,
+    sent: bool,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    alt: Option<f64>,
+    pressure_altitude: Option<f64>,
+    track: Option<f64>,
+    speed: Option<f64>,
+    magnetic_variation: Option<f64>,
+}
+
+impl Default for NmeaParser {
+    fn default() -> Self {
+        Self {
+            date: NaiveDate::from_ymd_opt(1970, 1, 1).expect("the Unix epoch is a valid date"),
+            fix: NmeaFix::default(),
+            expected_sentences: 0,
+            last_fix: None,
+        }
+    }
+}
+
+impl NmeaParser {
+    /// Returns the fixes completed by the line. Lines that are not valid NMEA 0183 are ignored.
+    pub fn parse_line(&mut self, line: &str) -> Vec<(DateTime<Utc>, GpsData)> {
+        let mut fixes = Vec::new();
+
+        let Some(fields) = Self::fields(line) else {
+            return fixes;
+        };
+
+        // the talker is not checked, so GN, GL and the other constellations are read as GP
+        let sentence = if fields[0].starts_with('P') {
+            fields[0]
+        } else {
+            fields[0].get(2..).unwrap_or_default()
+        };
+
+        match sentence {
+            "GGA" => {
+                let Some(time) = Self::time(fields.get(1)) else {
+                    return fixes;
+                };
+                fixes.extend(self.start_fix(time, NMEA_GGA));
+
+                // a fix quality of 0 means that the position is not valid
+                if fields.get(6).is_some_and(|quality| *quality != "0") {
+                    self.fix.lat = Self::coordinate(fields.get(2), fields.get(3));
+                    self.fix.lon = Self::coordinate(fields.get(4), fields.get(5));
+                    self.fix.alt = Self::number(fields.get(9));
+                }
+            }
+            "RMC" => {
+                let Some(time) = Self::time(fields.get(1)) else {
+                    return fixes;
+                };
+                if let Some(date) = fields
+                    .get(9)
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%d%m%y").ok())
+                {
+                    self.date = date;
+                }
+                fixes.extend(self.start_fix(time, NMEA_RMC));
+
+                if fields.get(2) == Some(&"A") {
+                    self.fix.lat = Self::coordinate(fields.get(3), fields.get(4));
+                    self.fix.lon = Self::coordinate(fields.get(5), fields.get(6));
+                    self.fix.speed = Self::number(fields.get(7))
+                        .map(|speed| speed / KNOTS_PER_METER_PER_SECOND)
+                        .or(self.fix.speed);
+                    self.fix.track = Self::number(fields.get(8)).or(self.fix.track);
+                    self.fix.magnetic_variation =
+                        Self::number(fields.get(10)).map(|variation| match fields.get(11) {
+                            Some(&"W") => -variation,
+                            _ => variation,
+                        });
+                }
+            }
+            "VTG" => {
+                self.fix.track = Self::number(fields.get(1)).or(self.fix.track);
+                self.fix.speed = Self::number(fields.get(7))
+                    .map(|speed| speed / 3.6)
+                    .or_else(|| {
+                        Self::number(fields.get(5)).map(|speed| speed / KNOTS_PER_METER_PER_SECOND)
+                    })
+                    .or(self.fix.speed);
+            }
+            "PGRMZ" => {
+                self.fix.pressure_altitude =
+                    Self::number(fields.get(1)).map(|altitude| match fields.get(2) {
+                        Some(&"f") => altitude / FEET_PER_METER,
+                        _ => altitude,
+                    });
+            }
+            _ => return fixes,
+        }
+
+        if self.expected_sentences != 0
+            && self.fix.sentences & self.expected_sentences == self.expected_sentences
+        {
+            fixes.extend(self.complete_fix());
+        }
+
+        fixes
+    }
+
+    /// Completes the last fix, which has no next one to end it.
+    pub fn finish(&mut self) -> Option<(DateTime<Utc>, GpsData)> {
+        self.complete_fix()
+    }
+
+    fn start_fix(&mut self, time: NaiveTime, sentence: u8) -> Option<(DateTime<Utc>, GpsData)> {
+        let mut completed = None;
+
+        if self.fix.time.is_some_and(|fix_time| fix_time != time) {
+            completed = self.complete_fix();
+            self.expected_sentences = self.fix.sentences;
+
+            // the speed, track and pressure altitude carry over for the sentences that only come once in a while
+            self.fix = NmeaFix {
+                pressure_altitude: self.fix.pressure_altitude,
+                track: self.fix.track,
+                speed: self.fix.speed,
+                ..NmeaFix::default()
+            };
+        }
+
+        self.fix.time = Some(time);
+        self.fix.sentences |= sentence;
+
+        completed
+    }
+
+    fn complete_fix(&mut self) -> Option<(DateTime<Utc>, GpsData)> {
+        if self.fix.sent {
+            return None;
+        }
+
+        let (Some(time), Some(lat), Some(lon)) = (self.fix.time, self.fix.lat, self.fix.lon) else {
+            return None;
+        };
+
+        let mut date = self.date.and_time(time).and_local_timezone(Utc).single()?;
+
+        // a log without RMC only has the time, so midnight is when it goes backwards
+        if let Some((last_date, _)) = self.last_fix {
+            if date < last_date - Duration::hours(12) {
+                date += Duration::days(1);
+                self.date += Duration::days(1);
+            }
+        }
+
+        let alt = self.fix.alt.unwrap_or_default();
+
+        let vertical_speed = match self.last_fix {
+            Some((last_date, last_alt)) if date > last_date => {
+                (alt - last_alt) / ((date - last_date).num_milliseconds() as f64 / 1000.0)
+            }
+            _ => 0.0,
+        };
+
+        self.fix.sent = true;
+        self.last_fix = Some((date, alt));
+
+        Some((
+            date,
+            GpsData {
+                lat,
+                lon,
+                alt,
+                pressure_altitude: self.fix.pressure_altitude.unwrap_or(alt),
+                gps_ground_true_track: self.fix.track.unwrap_or_default(),
+                gps_magnetic_variation: self.fix.magnetic_variation.unwrap_or_default(),
+                gps_ground_speed: self.fix.speed.unwrap_or_default(),
+                vertical_speed,
+            },
+        ))
+    }
+
+    /// The fields after the `$`, without the checksum, if the checksum is valid.
+    fn fields(line: &str) -> Option<Vec<&str>> {
+        let line = line.trim().strip_prefix('$')?;
+
+        let message = match line.split_once('*') {
+            Some((message, checksum)) => {
+                let checksum = u8::from_str_radix(checksum, 16).ok()?;
+
+                if message.bytes().fold(0u8, |acc, c| acc ^ c) != checksum {
+                    return None;
+                }
+
+                message
+            }
+            None => line,
+        };
+
+        Some(message.split(',').collect())
+    }
+
+    fn time(field: Option<&&str>) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(field?, "%H%M%S%.f").ok()
+    }
+
+    fn number(field: Option<&&str>) -> Option<f64> {
+        field?.parse().ok()
+    }
+
+    /// `ddmm.mmmm` or `dddmm.mmmm` with its hemisphere.
+    fn coordinate(field: Option<&&str>, hemisphere: Option<&&str>) -> Option<f64> {
+        let value = Self::number(field)?;
+        let degrees = (value / 100.0).trunc();
+        let coordinate = degrees + (value - degrees * 100.0) / 60.0;
+
+        match *hemisphere? {
+            "N" | "E" => Some(coordinate),
+            "S" | "W" => Some(-coordinate),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::NmeaParser;
+
+    #[test]
+    fn test_parse_line() {
+        let mut parser = NmeaParser::default();
+
+        let epoch = [
+            "$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,3.0,M,0.0,M,,*70\r\n",
+            "$GPRMC,211030.750,A,5130.5919,N,00007.0855,W,195.36,310.55,301022,5.0,E,S*07\r\n",
+            "$GPVTG,310.55,T,305.55,M,195.36,N,361.80,K,A*23\r\n",
+        ];
+
+        for line in epoch {
+            assert!(parser.parse_line(line).is_empty());
+        }
+
+        // the first fix is completed by the next one, which tells which sentences a fix has
+        let result = parser
+            .parse_line("$GPGGA,211031.750,5130.5919,N,00007.0855,W,1,12,1.0,5.0,M,0.0,M,,*77\r\n");
+
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        let (result_date, data) = &result[0];
+        assert_eq!(*result_date, date);
+        assert_eq!((data.lat * 1e6).round(), 51509865.0);
+        assert_eq!((data.lon * 1e6).round(), -118092.0);
+        assert_eq!(data.alt, 3.0);
+        assert_eq!(data.pressure_altitude, 3.0);
+        assert_eq!(data.gps_ground_true_track, 310.55);
+        assert_eq!(data.gps_magnetic_variation, 5.0);
+        assert_eq!((data.gps_ground_speed * 100.0).round(), 10050.0);
+        assert_eq!(data.vertical_speed, 0.0);
+
+        // the next fix is sent as soon as it has both GGA and RMC
+        let result = parser.parse_line(
+            "$GPRMC,211031.750,A,5130.5919,N,00007.0855,W,195.36,310.55,301022,5.0,W,S*14\r\n",
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].1.alt, 5.0);
+        assert_eq!(result[0].1.gps_magnetic_variation, -5.0);
+        assert_eq!(result[0].1.vertical_speed, 2.0);
+
+        assert!(parser.finish().is_none());
+    }
+
+    #[test]
+    fn test_parse_line_invalid() {
+        let mut parser = NmeaParser::default();
+
+        // a wrong checksum
+        assert!(parser
+            .parse_line("$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,3.0,M,0.0,M,,*71")
+            .is_empty());
+        // no fix
+        assert!(parser
+            .parse_line("$GNGGA,211030.750,,,,,0,00,99.9,,M,,M,,")
+            .is_empty());
+        assert!(parser.parse_line("garbage").is_empty());
+        assert!(parser.finish().is_none());
+    }
+
+    #[test]
+    fn test_finish() {
+        let mut parser = NmeaParser::default();
+
+        assert!(parser
+            .parse_line("$GNGGA,235959,5130.5919,N,00007.0855,W,1,12,1.0,100.0,M,0.0,M,,")
+            .is_empty());
+        assert!(parser.parse_line("$PGRMZ,1000,f,3").is_empty());
+
+        let (date, data) = parser.finish().unwrap();
+
+        // without RMC, the date starts on the Unix epoch
+        assert_eq!(date.timestamp(), 86399);
+        assert_eq!(data.alt, 100.0);
+        assert_eq!((data.pressure_altitude * 10.0).round(), 3048.0);
+
+        // the speed and track of VTG carry over to the next fix
+        assert!(parser.parse_line("$GNVTG,90.0,T,,M,,N,36.0,K,A").is_empty());
+
+        let result =
+            parser.parse_line("$GNGGA,000000,5130.5919,N,00007.0855,W,1,12,1.0,101.0,M,0.0,M,,");
+
+        assert_eq!(result.len(), 1);
+        let (date, data) = &result[0];
+        // and it moves on at midnight
+        assert_eq!(date.timestamp(), 86400);
+        assert_eq!(data.gps_ground_true_track, 90.0);
+        assert_eq!(data.gps_ground_speed, 10.0);
+        assert_eq!(data.vertical_speed, 1.0);
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use tracing::{error, instrument, trace};
+
+use super::{DataSource, NmeaParser, NmeaSourceConfig, NmeaStream, SourceEvent};
+
+const NMEA_SOURCE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The positions of a live NMEA 0183 stream, e.g. from a hardware GPS.
+#[derive(Debug)]
+pub struct NmeaSource {
+    rx: Receiver<SourceEvent>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl NmeaSource {
+    #[instrument(name = "NmeaSource::open")]
+    pub fn open(config: &NmeaSourceConfig) -> Result<Self, io::Error> {
+        let stream = NmeaStream::open(&config.input)?;
+
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        // the reads block, so they have their own thread instead of the actor's
+        let reader_shutdown = shutdown.clone();
+        thread::Builder::new()
+            .name("nmea-input".to_string())
+            .spawn(move || Self::read(stream, tx, reader_shutdown))?;
+
+        Ok(Self { rx, shutdown })
+    }
+
+    fn read(mut stream: NmeaStream, tx: Sender<SourceEvent>, shutdown: Arc<AtomicBool>) {
+        let mut parser = NmeaParser::default();
+
+        while !shutdown.load(Ordering::Relaxed) {
+            let lines = match stream.read_lines() {
+                Ok(lines) => lines,
+                Err(e) => {
+                    // the source notices that the channel has closed
+                    error!(error = ?e, "failed to read the NMEA input");
+                    return;
+                }
+            };
+
+            if lines.is_empty() {
+                continue;
+            }
+
+            // the input is alive as long as it sends something
+            let mut events = vec![SourceEvent::Ping];
+
+            for line in lines {
+                events.extend(parser.parse_line(&line).into_iter().map(|(_, data)| {
+                    trace!("NMEA input: Received GpsData");
+                    SourceEvent::Gps(data)
+                }));
+            }
+
+            for event in events {
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+
+        trace!("NMEA input closed");
+    }
+}
+
+impl DataSource for NmeaSource {
+    fn poll_interval(&self) -> Duration {
+        NMEA_SOURCE_POLL_INTERVAL
+    }
+
+    fn poll(&mut self) -> Result<Vec<SourceEvent>, io::Error> {
+        let mut events = Vec::new();
+
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => return Ok(events),
+                // the events received before the input closed are sent first
+                Err(TryRecvError::Disconnected) if !events.is_empty() => return Ok(events),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the NMEA input has closed",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for NmeaSource {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::TcpListener, thread, time::Duration};
+
+    use crate::input::{
+        DataSource, NmeaInputConfig, NmeaSourceConfig, NmeaTcpInputConfig, SourceEvent,
+    };
+
+    use super::NmeaSource;
+
+    #[test]
+    fn test_poll() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = NmeaSourceConfig {
+            input: NmeaInputConfig::Tcp(NmeaTcpInputConfig {
+                address: listener.local_addr().unwrap().to_string(),
+            }),
+        };
+
+        let mut source = NmeaSource::open(&config).unwrap();
+        let (mut client, _) = listener.accept().unwrap();
+
+        client
+            .write_all(b"$GNGGA,211030,5130.5919,N,00007.0855,W,1,12,1.0,100.0,M,0.0,M,,\r\n")
+            .unwrap();
+        client
+            .write_all(b"$GNGGA,211031,5130.5919,N,00007.0855,W,1,12,1.0,101.0,M,0.0,M,,\r\n")
+            .unwrap();
+        drop(client);
+
+        let mut events = Vec::new();
+        let error = loop {
+            match source.poll() {
+                Ok(polled) => events.extend(polled),
+                Err(e) => break e,
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        let positions = events
+            .iter()
+            .filter_map(|event| match event {
+                SourceEvent::Gps(data) => Some(data.alt),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(positions, vec![100.0, 101.0]);
+        assert!(matches!(events[0], SourceEvent::Ping));
+    }
+}
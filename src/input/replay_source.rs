@@ -0,0 +1,197 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, instrument};
+
+use crate::replay::{ReplayData, ReplayPlayback, ReplaySession};
+use crate::system::messages::ReplayControl;
+use crate::system::simconnect_objects::SimTime;
+
+use super::{DataSource, ReplaySourceConfig, SourceEvent, SourceOptions};
+
+const REPLAY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A recorded session, played back with its original timing.
+#[derive(Debug)]
+pub struct ReplaySource {
+    session: ReplaySession,
+    playback: ReplayPlayback,
+    last_poll: Option<Instant>,
+    // the recorders get the time each position was originally recorded at as the sim time
+    recording: bool,
+}
+
+impl ReplaySource {
+    #[instrument(name = "ReplaySource::open")]
+    pub fn open(config: &ReplaySourceConfig, options: SourceOptions) -> Result<Self, io::Error> {
+        let session = ReplaySession::load(Path::new(&config.path))?;
+
+        Ok(Self::new(session, config.speed, options.recording))
+    }
+
+    pub fn new(session: ReplaySession, speed: f64, recording: bool) -> Self {
+        Self {
+            session,
+            playback: ReplayPlayback::new(speed),
+            last_poll: None,
+            recording,
+        }
+    }
+}
+
+impl DataSource for ReplaySource {
+    fn poll_interval(&self) -> Duration {
+        REPLAY_POLL_INTERVAL
+    }
+
+    fn poll(&mut self) -> Result<Vec<SourceEvent>, io::Error> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_poll
+            .replace(now)
+            .map(|last_poll| now - last_poll)
+            .unwrap_or_default();
+
+        let frames = self.session.frames();
+        let due = self.playback.advance(elapsed, frames);
+
+        // a paused replay is still alive
+        let mut events = vec![SourceEvent::Ping];
+
+        for frame in &frames[due] {
+            match &frame.data {
+                ReplayData::Gps(data) => {
+                    if self.recording {
+                        let date = self.session.date(frame);
+                        events.push(SourceEvent::SimTime(SimTime::from_date_time(&date)));
+                    }
+
+                    events.push(SourceEvent::Gps(data.clone()));
+                }
+                ReplayData::Attitude(data) => events.push(SourceEvent::Attitude(data.clone())),
+            }
+        }
+
+        if self.playback.is_finished(frames) {
+            info!("Replay reached the end of the session");
+            events.push(SourceEvent::Quit(
+                "the replay has reached the end of the session".to_string(),
+            ));
+        }
+
+        Ok(events)
+    }
+
+    fn control(&mut self, control: ReplayControl) {
+        match control {
+            ReplayControl::Pause(paused) => self.playback.set_paused(paused),
+            ReplayControl::Seek(position) => {
+                self.playback.seek(position, self.session.frames());
+            }
+            ReplayControl::Speed(speed) => self.playback.set_speed(speed),
+        }
+
+        info!(control = ?control, position = ?self.playback.position(), "Replay updated");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::input::{DataSource, SourceEvent};
+    use crate::replay::{ReplayData, ReplaySession};
+    use crate::system::messages::ReplayControl;
+    use crate::system::simconnect_objects::GpsData;
+
+    use super::ReplaySource;
+
+    #[test]
+    fn test_poll() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let session = ReplaySession::from_records(vec![
+            (date, ReplayData::Gps(data.clone())),
+            (
+                date + chrono::Duration::seconds(60),
+                ReplayData::Gps(data.clone()),
+            ),
+            (date + chrono::Duration::seconds(120), ReplayData::Gps(data)),
+        ])
+        .unwrap();
+
+        let mut source = ReplaySource::new(session, 1.0, false);
+
+        let events = source.poll().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], SourceEvent::Ping));
+        assert!(matches!(events[1], SourceEvent::Gps(_)));
+
+        source.control(ReplayControl::Pause(true));
+        source.control(ReplayControl::Seek(Duration::from_secs(90)));
+
+        let events = source.poll().unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], SourceEvent::Ping));
+
+        source.control(ReplayControl::Seek(Duration::from_secs(120)));
+
+        let events = source.poll().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[1], SourceEvent::Gps(_)));
+        assert!(matches!(events[2], SourceEvent::Quit(_)));
+    }
+
+    #[test]
+    fn test_poll_recording() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let session = ReplaySession::from_records(vec![(date, ReplayData::Gps(data))]).unwrap();
+
+        let mut source = ReplaySource::new(session, 1.0, true);
+
+        let events = source.poll().unwrap();
+        assert_eq!(events.len(), 4);
+        match &events[1] {
+            SourceEvent::SimTime(sim_time) => assert_eq!(sim_time.as_date_time(), Some(date)),
+            event => panic!("unexpected event {event:?}"),
+        }
+        assert!(matches!(events[2], SourceEvent::Gps(_)));
+        assert!(matches!(events[3], SourceEvent::Quit(_)));
+    }
+}
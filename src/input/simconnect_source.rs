@@ -0,0 +1,186 @@
+use std::{io, time::Duration};
+
+use simconnect_sdk::{
+    Condition, Notification, Period, SimConnect, SimConnectError, SystemEvent, SystemEventRequest,
+};
+use tracing::{error, info, instrument, trace};
+
+use crate::system::messages::RefreshRate;
+use crate::system::simconnect_objects::{Airport, AttitudeData, GpsData, OnGround, SimTime};
+
+use super::{DataSource, SourceEvent, SourceOptions};
+
+/// The data of Microsoft Flight Simulator, received through SimConnect.
+#[derive(Debug)]
+pub struct SimConnectSource {
+    sc: SimConnect,
+    options: SourceOptions,
+}
+
+impl SimConnectSource {
+    #[instrument(name = "SimConnectSource::open")]
+    pub fn open(options: SourceOptions) -> Result<Self, io::Error> {
+        let sc = SimConnect::new("Simple Program").map_err(|e| {
+            error!(error = ?e, "SimConnect SDK Error");
+            Self::io_error(e)
+        })?;
+
+        info!("SimConnect SDK: Connected");
+
+        Ok(Self { sc, options })
+    }
+
+    fn register(&mut self) -> Result<(), SimConnectError> {
+        let (period, interval) = match self.options.refresh_rate {
+            RefreshRate::Fast => (Period::VisualFrame, 6u32),
+            RefreshRate::Slow => (Period::Second, 0u32),
+        };
+
+        let request_id = self.sc.register_object::<GpsData>()?;
+        self.sc.request_data_on_sim_object(
+            request_id,
+            period.clone(),
+            Condition::None,
+            interval,
+        )?;
+
+        // a broadcaster with its own attitude rate gets the attitude of every frame and keeps to its rate,
+        // which the position period would otherwise cap
+        let (attitude_period, attitude_interval) = match self.options.attitude_rate_hz {
+            Some(_) => (Period::VisualFrame, 0u32),
+            None => (period.clone(), interval),
+        };
+
+        let request_id = self.sc.register_object::<AttitudeData>()?;
+        self.sc.request_data_on_sim_object(
+            request_id,
+            attitude_period,
+            Condition::None,
+            attitude_interval,
+        )?;
+
+        if self.options.recording {
+            // the sim time is requested as often as the position it timestamps
+            let request_id = self.sc.register_object::<SimTime>()?;
+            self.sc
+                .request_data_on_sim_object(request_id, period, Condition::None, interval)?;
+        }
+
+        // the recorders keep the raw telemetry of every message
+        if self.options.landing_detection_enabled || self.options.recording {
+            self.sc.register_object::<OnGround>()?;
+
+            // subscribe to the airport list
+            self.sc
+                .subscribe_to_facilities(simconnect_sdk::FacilityType::Airport)?;
+        }
+
+        self.sc
+            .subscribe_to_system_event(SystemEventRequest::FourSeconds)?;
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self) -> Result<Vec<SourceEvent>, SimConnectError> {
+        let mut events = Vec::new();
+
+        // everything received since the last poll, so that a frame-rate attitude does not pile up
+        while let Some(notification) = self.sc.get_next_dispatch()? {
+            if let Some(event) = self.handle(notification)? {
+                let quit = matches!(event, SourceEvent::Quit(_));
+                events.push(event);
+
+                if quit {
+                    break;
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn handle(
+        &mut self,
+        notification: Notification,
+    ) -> Result<Option<SourceEvent>, SimConnectError> {
+        let event = match notification {
+            Notification::Open => {
+                info!("SimConnect SDK: Received Client Open");
+
+                self.register()?;
+
+                SourceEvent::Ping
+            }
+            Notification::Quit => {
+                info!("SimConnect SDK: Received Quit");
+
+                SourceEvent::Quit("SimConnect SDK: Received Quit".to_string())
+            }
+            Notification::Object(data) => {
+                if let Ok(gps_data) = GpsData::try_from(&data) {
+                    trace!("SimConnect SDK: Received GpsData");
+                    SourceEvent::Gps(gps_data)
+                } else if let Ok(attitude_data) = AttitudeData::try_from(&data) {
+                    trace!("SimConnect SDK: Received AttitudeData");
+                    SourceEvent::Attitude(attitude_data)
+                } else if let Ok(on_ground_data) = OnGround::try_from(&data) {
+                    trace!("SimConnect SDK: Received OnGround");
+                    SourceEvent::OnGround(on_ground_data)
+                } else if let Ok(sim_time) = SimTime::try_from(&data) {
+                    trace!("SimConnect SDK: Received SimTime");
+                    SourceEvent::SimTime(sim_time)
+                } else {
+                    return Ok(None);
+                }
+            }
+            Notification::AirportList(airports) => {
+                trace!("SimConnect SDK: Received AirportList");
+                SourceEvent::Airports(airports.into_iter().map(Airport::from).collect())
+            }
+            Notification::SystemEvent(event) => {
+                trace!("SimConnect SDK: Received SystemEvent");
+
+                if event != SystemEvent::FourSeconds {
+                    return Ok(None);
+                }
+
+                SourceEvent::Ping
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+
+    fn io_error(e: SimConnectError) -> io::Error {
+        io::Error::other(e)
+    }
+}
+
+impl From<simconnect_sdk::Airport> for Airport {
+    fn from(airport: simconnect_sdk::Airport) -> Self {
+        Self {
+            icao: airport.icao,
+            lat: airport.lat,
+            lon: airport.lon,
+            alt: airport.alt,
+        }
+    }
+}
+
+impl DataSource for SimConnectSource {
+    fn poll_interval(&self) -> Duration {
+        match self.options.refresh_rate {
+            _ if self.options.attitude_rate_hz.is_some() => Duration::from_millis(20),
+            RefreshRate::Fast => Duration::from_millis(20),
+            RefreshRate::Slow => Duration::from_millis(200),
+        }
+    }
+
+    fn poll(&mut self) -> Result<Vec<SourceEvent>, io::Error> {
+        self.dispatch().map_err(|e| {
+            error!(error = ?e, "SimConnect SDK Error");
+            Self::io_error(e)
+        })
+    }
+}
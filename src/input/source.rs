@@ -0,0 +1,63 @@
+use std::{fmt, io, time::Duration};
+
+use tracing::warn;
+
+use crate::system::messages::{RefreshRate, ReplayControl};
+#[cfg(windows)]
+use crate::system::simconnect_objects::{Airport, OnGround};
+use crate::system::simconnect_objects::{AttitudeData, GpsData, SimTime};
+
+#[cfg(windows)]
+use super::SimConnectSource;
+use super::{NmeaSource, ReplaySource, SourceConfig, SyntheticSource};
+
+#[derive(Debug, Clone)]
+pub enum SourceEvent {
+    Gps(GpsData),
+    Attitude(AttitudeData),
+    // only the simulator knows whether the aircraft is on the ground and where the airports are
+    #[cfg(windows)]
+    OnGround(OnGround),
+    #[cfg(windows)]
+    Airports(Vec<Airport>),
+    SimTime(SimTime),
+    // the source is alive, even if it has nothing new to send
+    Ping,
+    // the source has nothing more to send, e.g. the simulator has quit
+    Quit(String),
+}
+
+/// What the rest of the session needs from the source.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceOptions {
+    pub refresh_rate: RefreshRate,
+    pub landing_detection_enabled: bool,
+    pub recording: bool,
+    // the highest rate at which a broadcaster sends the attitude
+    pub attitude_rate_hz: Option<u32>,
+}
+
+/// Where the data of a session comes from. The source actor polls it and sends its events
+/// to the broadcasters, the recorders and the landing detection.
+pub trait DataSource: fmt::Debug {
+    fn poll_interval(&self) -> Duration;
+
+    /// Returns the events received since the last poll.
+    fn poll(&mut self) -> Result<Vec<SourceEvent>, io::Error>;
+
+    fn control(&mut self, control: ReplayControl) {
+        warn!(control = ?control, "the data source cannot be controlled");
+    }
+}
+
+impl SourceConfig {
+    pub fn open(&self, options: SourceOptions) -> Result<Box<dyn DataSource>, io::Error> {
+        Ok(match self {
+            #[cfg(windows)]
+            SourceConfig::SimConnect => Box::new(SimConnectSource::open(options)?),
+            SourceConfig::Replay(config) => Box::new(ReplaySource::open(config, options)?),
+            SourceConfig::Nmea(config) => Box::new(NmeaSource::open(config)?),
+            SourceConfig::Synthetic(config) => Box::new(SyntheticSource::new(config, options)),
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    io::{self, Read},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use tracing::{error, info, instrument};
+
+use super::NmeaInputConfig;
+
+// the reads time out, so the reading thread can notice that it has to stop
+const NMEA_INPUT_READ_TIMEOUT: Duration = Duration::from_millis(200);
+const NMEA_INPUT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// a line is at most 82 characters, so anything longer is not NMEA
+const NMEA_INPUT_MAX_LINE_LENGTH: usize = 1024;
+
+/// The lines of a live NMEA 0183 stream from a serial port, a TCP server or UDP datagrams.
+pub struct NmeaStream {
+    reader: NmeaReader,
+    buffer: Vec<u8>,
+}
+
+enum NmeaReader {
+    Stream(Box<dyn Read + Send>),
+    Udp(UdpSocket),
+}
+
+impl NmeaStream {
+    #[instrument(name = "NmeaStream::open")]
+    pub fn open(config: &NmeaInputConfig) -> Result<Self, io::Error> {
+        let reader = match config {
+            NmeaInputConfig::Serial(config) => {
+                let port = serialport::new(&config.port, config.baud_rate)
+                    .timeout(NMEA_INPUT_READ_TIMEOUT)
+                    .open()
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to open the NMEA input COM port");
+                        io::Error::from(e)
+                    })?;
+
+                NmeaReader::Stream(Box::new(port))
+            }
+            NmeaInputConfig::Tcp(config) => {
+                let address = config
+                    .address
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the NMEA input address does not resolve",
+                        )
+                    })
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to resolve the NMEA input address");
+                        e
+                    })?;
+
+                let stream = TcpStream::connect_timeout(&address, NMEA_INPUT_CONNECT_TIMEOUT)
+                    .map_err(|e| {
+                        error!(error = ?e, "failed to connect to the NMEA input");
+                        e
+                    })?;
+                stream.set_read_timeout(Some(NMEA_INPUT_READ_TIMEOUT))?;
+
+                NmeaReader::Stream(Box::new(stream))
+            }
+            NmeaInputConfig::Udp(config) => {
+                let socket = UdpSocket::bind((config.bind.as_str(), config.port)).map_err(|e| {
+                    error!(error = ?e, "failed to bind the NMEA input socket");
+                    e
+                })?;
+                socket.set_read_timeout(Some(NMEA_INPUT_READ_TIMEOUT))?;
+
+                NmeaReader::Udp(socket)
+            }
+        };
+
+        info!("NMEA input opened");
+
+        Ok(Self {
+            reader,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns the complete lines received so far, which are none if the read has timed out.
+    pub fn read_lines(&mut self) -> Result<Vec<String>, io::Error> {
+        let mut chunk = [0u8; 1024];
+
+        let result = match &mut self.reader {
+            NmeaReader::Stream(stream) => stream.read(&mut chunk),
+            NmeaReader::Udp(socket) => socket.recv(&mut chunk),
+        };
+
+        let length = match result {
+            Ok(0) if matches!(self.reader, NmeaReader::Stream(_)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the NMEA input has closed",
+                ))
+            }
+            Ok(length) => length,
+            Err(ref e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::TimedOut
+                        | io::ErrorKind::WouldBlock
+                        | io::ErrorKind::Interrupted
+                ) =>
+            {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.buffer.extend_from_slice(&chunk[..length]);
+
+        // a datagram holds whole sentences, even if the last one does not end the line
+        if matches!(self.reader, NmeaReader::Udp(_)) {
+            self.buffer.push(b'\n');
+        }
+
+        Ok(self.take_lines())
+    }
+
+    fn take_lines(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        while let Some(end) = self.buffer.iter().position(|c| *c == b'\n') {
+            let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+
+            if !line.trim().is_empty() {
+                lines.push(line.trim().to_string());
+            }
+        }
+
+        if self.buffer.len() > NMEA_INPUT_MAX_LINE_LENGTH {
+            self.buffer.clear();
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::TcpListener};
+
+    use crate::input::{NmeaInputConfig, NmeaTcpInputConfig};
+
+    use super::NmeaStream;
+
+    #[test]
+    fn test_read_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = NmeaInputConfig::Tcp(NmeaTcpInputConfig {
+            address: listener.local_addr().unwrap().to_string(),
+        });
+
+        let mut stream = NmeaStream::open(&config).unwrap();
+        let (mut client, _) = listener.accept().unwrap();
+
+        client.write_all(b"$GPGGA,1*00\r\n$GPRMC,").unwrap();
+        client.flush().unwrap();
+
+        let mut lines = Vec::new();
+        while lines.is_empty() {
+            lines = stream.read_lines().unwrap();
+        }
+        assert_eq!(lines, vec!["$GPGGA,1*00"]);
+
+        client.write_all(b"2*00\r\n").unwrap();
+        drop(client);
+
+        let mut lines = Vec::new();
+        while lines.is_empty() {
+            lines = stream.read_lines().unwrap();
+        }
+        assert_eq!(lines, vec!["$GPRMC,2*00"]);
+
+        assert!(stream.read_lines().is_err());
+    }
+}
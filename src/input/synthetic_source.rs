@@ -0,0 +1,195 @@
+use std::{
+    f64::consts::PI,
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::system::messages::RefreshRate;
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{DataSource, SourceEvent, SourceOptions, SyntheticSourceConfig};
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+const GRAVITY_M_S2: f64 = 9.80665;
+
+/// A generated flight, which circles clockwise around the center of the config.
+#[derive(Debug)]
+pub struct SyntheticSource {
+    config: SyntheticSourceConfig,
+    interval: Duration,
+    // a broadcaster with its own attitude rate gets the attitude more often than the position
+    attitude_interval: Option<Duration>,
+    start: Instant,
+    last_gps: Option<Instant>,
+}
+
+impl SyntheticSource {
+    pub fn new(config: &SyntheticSourceConfig, options: SourceOptions) -> Self {
+        let interval = match options.refresh_rate {
+            RefreshRate::Fast => Duration::from_millis(100),
+            RefreshRate::Slow => Duration::from_secs(1),
+        };
+
+        let attitude_interval = options
+            .attitude_rate_hz
+            .filter(|rate| *rate > 0)
+            .map(|rate| Duration::from_secs(1) / rate)
+            .filter(|attitude_interval| *attitude_interval < interval);
+
+        Self {
+            config: config.clone(),
+            interval,
+            attitude_interval,
+            start: Instant::now(),
+            last_gps: None,
+        }
+    }
+
+    fn should_send_gps(&self, now: Instant) -> bool {
+        match (self.attitude_interval, self.last_gps) {
+            // half a poll of leeway, so that the position keeps to its interval despite the timer jitter
+            (Some(attitude_interval), Some(last_gps)) => {
+                now - last_gps + attitude_interval / 2 >= self.interval
+            }
+            _ => true,
+        }
+    }
+
+    pub fn position_at(&self, elapsed: Duration) -> (GpsData, AttitudeData) {
+        let radius = self.config.radius.max(1.0);
+
+        // the bearing of the aircraft from the center, starting north of it
+        let bearing = (self.config.speed / radius * elapsed.as_secs_f64()) % (2.0 * PI);
+
+        let lat = self.config.lat + (radius * bearing.cos() / EARTH_RADIUS_M).to_degrees();
+        let lon = self.config.lon
+            + (radius * bearing.sin() / (EARTH_RADIUS_M * self.config.lat.to_radians().cos()))
+                .to_degrees();
+        let track = (bearing.to_degrees() + 90.0) % 360.0;
+
+        let gps_data = GpsData {
+            lat,
+            lon,
+            alt: self.config.alt,
+            pressure_altitude: self.config.alt,
+            gps_ground_true_track: track,
+            gps_magnetic_variation: 0.0,
+            gps_ground_speed: self.config.speed,
+            vertical_speed: 0.0,
+        };
+
+        // a coordinated turn to the right, which SimConnect reports as a negative bank
+        let bank = (self.config.speed.powi(2) / (radius * GRAVITY_M_S2))
+            .atan()
+            .to_degrees();
+
+        let attitude_data = AttitudeData {
+            true_heading: track,
+            pitch: 0.0,
+            bank: -bank,
+        };
+
+        (gps_data, attitude_data)
+    }
+}
+
+impl DataSource for SyntheticSource {
+    fn poll_interval(&self) -> Duration {
+        self.attitude_interval.unwrap_or(self.interval)
+    }
+
+    fn poll(&mut self) -> Result<Vec<SourceEvent>, io::Error> {
+        let now = Instant::now();
+        let (gps_data, attitude_data) = self.position_at(now - self.start);
+
+        let mut events = vec![SourceEvent::Ping];
+
+        if self.should_send_gps(now) {
+            events.push(SourceEvent::Gps(gps_data));
+            self.last_gps.replace(now);
+        }
+
+        events.push(SourceEvent::Attitude(attitude_data));
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::input::{DataSource, SourceEvent, SourceOptions, SyntheticSourceConfig};
+    use crate::system::messages::RefreshRate;
+
+    use super::SyntheticSource;
+
+    #[test]
+    fn test_position_at() {
+        let config = SyntheticSourceConfig {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            speed: 60.0,
+            radius: 2000.0,
+        };
+
+        let source = SyntheticSource::new(
+            &config,
+            SourceOptions {
+                refresh_rate: RefreshRate::Slow,
+                landing_detection_enabled: false,
+                recording: false,
+                attitude_rate_hz: None,
+            },
+        );
+
+        let (gps_data, attitude_data) = source.position_at(Duration::ZERO);
+
+        assert!((gps_data.lat - 51.527851).abs() < 1e-6);
+        assert!((gps_data.lon - -0.118092).abs() < 1e-6);
+        assert_eq!(gps_data.gps_ground_true_track, 90.0);
+        assert_eq!(gps_data.gps_ground_speed, 60.0);
+        assert!((attitude_data.roll_right() - 10.40).abs() < 0.01);
+
+        // a quarter of the circle later, the aircraft is east of the center and flies south
+        let quarter = Duration::from_secs_f64(2000.0 * std::f64::consts::PI / 2.0 / 60.0);
+        let (gps_data, _) = source.position_at(quarter);
+
+        assert!((gps_data.lat - 51.509865).abs() < 1e-6);
+        assert!(gps_data.lon > -0.118092);
+        assert!((gps_data.gps_ground_true_track - 180.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_poll_with_attitude_rate() {
+        let config = SyntheticSourceConfig {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 1000.0,
+            speed: 60.0,
+            radius: 2000.0,
+        };
+
+        let mut source = SyntheticSource::new(
+            &config,
+            SourceOptions {
+                refresh_rate: RefreshRate::Slow,
+                landing_detection_enabled: false,
+                recording: false,
+                attitude_rate_hz: Some(10),
+            },
+        );
+
+        assert_eq!(source.poll_interval(), Duration::from_millis(100));
+
+        let events = source.poll().unwrap();
+        assert!(matches!(events[1], SourceEvent::Gps(_)));
+        assert!(matches!(events[2], SourceEvent::Attitude(_)));
+
+        // the position keeps to the refresh rate
+        let events = source.poll().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], SourceEvent::Attitude(_)));
+    }
+}
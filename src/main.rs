@@ -15,9 +15,15 @@ use tracing_subscriber::Registry;
 
 mod broadcaster;
 mod cmd;
+mod input;
+mod recorder;
+mod replay;
 mod system;
 
-use cmd::{cmd_get_available_com_ports, cmd_get_status, cmd_start, cmd_stop, AppState};
+use cmd::{
+    cmd_get_available_com_ports, cmd_get_broadcasters_status, cmd_get_status, cmd_pause_replay,
+    cmd_seek_replay, cmd_set_replay_speed, cmd_start, cmd_stop, AppState,
+};
 use system::{coordinator_actor::CoordinatorActor, messages::CoordinatorMessage};
 
 #[actix::main]
@@ -47,7 +53,11 @@ fn setup_app() {
             cmd_get_available_com_ports,
             cmd_start,
             cmd_stop,
-            cmd_get_status
+            cmd_get_status,
+            cmd_get_broadcasters_status,
+            cmd_pause_replay,
+            cmd_seek_replay,
+            cmd_set_replay_speed
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
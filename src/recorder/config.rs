@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum RecorderConfig {
+    Gpx(GpxConfig),
+    Igc(IgcConfig),
+    Telemetry(TelemetryRecorderConfig),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpxConfig {
+    // every session is recorded to a new file in this directory
+    pub directory: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgcConfig {
+    // every session is recorded to a new file in this directory
+    pub directory: String,
+    #[serde(default)]
+    pub pilot: String,
+    #[serde(default)]
+    pub glider_type: String,
+    #[serde(default)]
+    pub glider_id: String,
+    #[serde(default = "default_igc_interval_seconds")]
+    pub interval_seconds: u32,
+    #[serde(default)]
+    pub security_record: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryRecorderConfig {
+    // every session is recorded to new files in this directory
+    pub directory: String,
+    #[serde(default)]
+    pub format: TelemetryFormat,
+    // a limit of 0 disables the rotation by size
+    #[serde(default = "default_telemetry_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    // a limit of 0 disables the rotation by duration
+    #[serde(default = "default_telemetry_max_file_duration_minutes")]
+    pub max_file_duration_minutes: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TelemetryFormat {
+    #[default]
+    JsonLines,
+    Csv,
+}
+
+fn default_igc_interval_seconds() -> u32 {
+    1
+}
+
+fn default_telemetry_max_file_size_mb() -> u64 {
+    100
+}
+
+fn default_telemetry_max_file_duration_minutes() -> u64 {
+    60
+}
@@ -0,0 +1,26 @@
+use std::{fmt, io};
+
+use crate::system::simconnect_objects::{Airport, AttitudeData, GpsData, OnGround, SimTime};
+
+pub trait RecorderExt: fmt::Debug {
+    fn record(&mut self, data: GpsData) -> Result<(), io::Error>;
+
+    fn record_attitude(&mut self, _data: AttitudeData) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn record_on_ground(&mut self, _data: OnGround) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn record_airports(&mut self, _data: Vec<Airport>) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn record_sim_time(&mut self, _data: SimTime) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    /// Completes the recording once the session ends.
+    fn finish(&mut self) -> Result<(), io::Error>;
+}
@@ -0,0 +1,205 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tracing::{error, info, instrument, trace};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{GpxConfig, RecorderExt};
+
+/// Records the session as a GPX 1.1 track, with the speed and course in Garmin's `TrackPointExtension`.
+#[derive(Debug)]
+pub struct Gpx {
+    // taken once the track has been completed
+    writer: Option<BufWriter<File>>,
+}
+
+impl Gpx {
+    #[instrument(name = "Gpx::new")]
+    pub fn new(config: GpxConfig) -> Result<Box<dyn RecorderExt>, io::Error> {
+        Ok(Box::new(Self::create(
+            Path::new(&config.directory),
+            &Utc::now(),
+        )?))
+    }
+
+    fn create(directory: &Path, date: &DateTime<Utc>) -> Result<Self, io::Error> {
+        fs::create_dir_all(directory).map_err(|e| {
+            error!(error = ?e, "failed to create the GPX directory");
+            e
+        })?;
+
+        let path = directory.join(date.format("msfs-%Y%m%d-%H%M%S.gpx").to_string());
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| {
+                error!(error = ?e, path = ?path, "failed to create the GPX file");
+                e
+            })?;
+
+        let mut writer = BufWriter::new(file);
+        writer.write_all(Self::header(date).as_bytes())?;
+        writer.flush()?;
+
+        info!(path = ?path, "Recording the GPX track");
+
+        Ok(Gpx {
+            writer: Some(writer),
+        })
+    }
+}
+
+impl RecorderExt for Gpx {
+    #[instrument(name = "Gpx::record", skip(self, data))]
+    fn record(&mut self, data: GpsData) -> Result<(), io::Error> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(Self::track_point(&Utc::now(), &data).as_bytes())?;
+            // flushed on every point, so only the closing tags are missing if the app is killed
+            writer.flush()?;
+
+            trace!("Successfully recorded the GPX track point");
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Gpx::finish", skip(self))]
+    fn finish(&mut self) -> Result<(), io::Error> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.write_all(Self::footer().as_bytes())?;
+            writer.flush()?;
+
+            info!("GPX track completed");
+        }
+
+        Ok(())
+    }
+}
+
+impl Gpx {
+    fn header(date: &DateTime<Utc>) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <gpx version=\"1.1\" creator=\"MSFS 2020 GPS Link\" \
+             xmlns=\"http://www.topografix.com/GPX/1/1\" \
+             xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v2\">\n\
+             <metadata>\n\
+             <time>{}</time>\n\
+             </metadata>\n\
+             <trk>\n\
+             <name>MSFS {}</name>\n\
+             <trkseg>\n",
+            date.to_rfc3339_opts(SecondsFormat::Secs, true),
+            date.format("%Y-%m-%d %H:%M")
+        )
+    }
+
+    fn track_point(date: &DateTime<Utc>, data: &GpsData) -> String {
+        format!(
+            "<trkpt lat=\"{:.7}\" lon=\"{:.7}\">\
+             <ele>{:.1}</ele>\
+             <time>{}</time>\
+             <extensions><gpxtpx:TrackPointExtension>\
+             <gpxtpx:speed>{:.2}</gpxtpx:speed>\
+             <gpxtpx:course>{:.1}</gpxtpx:course>\
+             </gpxtpx:TrackPointExtension></extensions>\
+             </trkpt>\n",
+            data.lat,
+            data.lon,
+            data.alt,
+            date.to_rfc3339_opts(SecondsFormat::Millis, true),
+            data.gps_ground_speed,
+            data.gps_ground_true_track.rem_euclid(360.0)
+        )
+    }
+
+    fn footer() -> &'static str {
+        "</trkseg>\n</trk>\n</gpx>\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::recorder::RecorderExt;
+
+    use super::Gpx;
+
+    #[test]
+    fn test_track_point() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Gpx::track_point(&date, &data);
+
+        assert_eq!(
+            result,
+            "<trkpt lat=\"51.5098650\" lon=\"-0.1180920\"><ele>100.5</ele><time>2022-10-30T21:10:30.750Z</time>\
+             <extensions><gpxtpx:TrackPointExtension><gpxtpx:speed>100.50</gpxtpx:speed><gpxtpx:course>310.6</gpxtpx:course></gpxtpx:TrackPointExtension></extensions>\
+             </trkpt>\n"
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        let directory = std::env::temp_dir().join(format!("msfs-gpx-{}", std::process::id()));
+
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let mut gpx = Gpx::create(&directory, &date).unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        gpx.record(data.clone()).unwrap();
+        gpx.record(data).unwrap();
+        gpx.finish().unwrap();
+        // a second stop does not complete the track again
+        gpx.finish().unwrap();
+
+        let result = fs::read_to_string(directory.join("msfs-20221030-211030.gpx")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(
+            result.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\"")
+        );
+        assert!(result.contains("<time>2022-10-30T21:10:30Z</time>"));
+        assert_eq!(result.matches("<trkpt ").count(), 2);
+        assert!(result.ends_with("</trkpt>\n</trkseg>\n</trk>\n</gpx>\n"));
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::{error, info, instrument, trace};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::{IgcConfig, RecorderExt};
+
+// XXX is the manufacturer code reserved for loggers without an approved code
+const IGC_MANUFACTURER_CODE: &str = "XXX";
+const IGC_LOGGER_ID: &str = "MSF";
+const IGC_MAX_FLIGHTS_PER_DAY: u32 = 99;
+const IGC_FIX_ACCURACY_M: u32 = GpsData::POSITION_ACCURACY_M as u32;
+// the files are not signed, so the security record only marks where a signature would go
+const IGC_SECURITY_PLACEHOLDER: &str = "MSFS2020GPSLINKUNSIGNED";
+
+/// Records the session as an IGC flight log, with the pressure and GNSS altitudes of every fix.
+#[derive(Debug)]
+pub struct Igc {
+    // taken once the log has been completed
+    writer: Option<BufWriter<File>>,
+    interval: Duration,
+    security_record: bool,
+    last_fix: Option<DateTime<Utc>>,
+}
+
+impl Igc {
+    #[instrument(name = "Igc::new")]
+    pub fn new(config: IgcConfig) -> Result<Box<dyn RecorderExt>, io::Error> {
+        Ok(Box::new(Self::create(&config, &Utc::now())?))
+    }
+
+    fn create(config: &IgcConfig, date: &DateTime<Utc>) -> Result<Self, io::Error> {
+        let directory = Path::new(&config.directory);
+
+        fs::create_dir_all(directory).map_err(|e| {
+            error!(error = ?e, "failed to create the IGC directory");
+            e
+        })?;
+
+        let (file, flight) = Self::create_file(directory, date).map_err(|e| {
+            error!(error = ?e, "failed to create the IGC file");
+            e
+        })?;
+
+        let mut writer = BufWriter::new(file);
+        writer.write_all(Self::header(config, date, flight).as_bytes())?;
+        writer.flush()?;
+
+        info!(flight = flight, "Recording the IGC flight log");
+
+        Ok(Igc {
+            writer: Some(writer),
+            interval: Duration::seconds(config.interval_seconds.max(1).into()),
+            security_record: config.security_record,
+            last_fix: None,
+        })
+    }
+
+    /// Creates the file with the long IGC file name, numbering the flights of the day from 1.
+    fn create_file(directory: &Path, date: &DateTime<Utc>) -> Result<(File, u32), io::Error> {
+        for flight in 1..=IGC_MAX_FLIGHTS_PER_DAY {
+            let path = directory.join(format!(
+                "{}-{}-{}-{:02}.igc",
+                date.format("%Y-%m-%d"),
+                IGC_MANUFACTURER_CODE,
+                IGC_LOGGER_ID,
+                flight
+            ));
+
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(file) => return Ok((file, flight)),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "all the IGC flight numbers of the day are taken",
+        ))
+    }
+}
+
+impl RecorderExt for Igc {
+    #[instrument(name = "Igc::record", skip(self, data))]
+    fn record(&mut self, data: GpsData) -> Result<(), io::Error> {
+        let date = Utc::now();
+
+        if !self.should_record_fix(&date) {
+            return Ok(());
+        }
+
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(Self::fix(&date, &data).as_bytes())?;
+            // flushed on every fix, so only the security record is missing if the app is killed
+            writer.flush()?;
+
+            self.last_fix.replace(date);
+
+            trace!("Successfully recorded the IGC fix");
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Igc::finish", skip(self))]
+    fn finish(&mut self) -> Result<(), io::Error> {
+        if let Some(mut writer) = self.writer.take() {
+            if self.security_record {
+                writer.write_all(format!("G{IGC_SECURITY_PLACEHOLDER}\r\n").as_bytes())?;
+            }
+            writer.flush()?;
+
+            info!("IGC flight log completed");
+        }
+
+        Ok(())
+    }
+}
+
+impl Igc {
+    fn should_record_fix(&self, date: &DateTime<Utc>) -> bool {
+        match self.last_fix {
+            Some(last_fix) => *date - last_fix >= self.interval,
+            None => true,
+        }
+    }
+
+    fn header(config: &IgcConfig, date: &DateTime<Utc>, flight: u32) -> String {
+        let records = [
+            format!("A{IGC_MANUFACTURER_CODE}{IGC_LOGGER_ID}MSFS 2020 GPS Link"),
+            format!("HFDTEDATE:{},{:02}", date.format("%d%m%y"), flight),
+            format!("HFFXA{IGC_FIX_ACCURACY_M:03}"),
+            format!("HFPLTPILOTINCHARGE:{}", Self::sanitize(&config.pilot)),
+            format!("HFGTYGLIDERTYPE:{}", Self::sanitize(&config.glider_type)),
+            format!("HFGIDGLIDERID:{}", Self::sanitize(&config.glider_id)),
+            "HFDTMGPSDATUM:WGS84".to_string(),
+            format!("HFRFWFIRMWAREVERSION:{}", env!("CARGO_PKG_VERSION")),
+            "HFRHWHARDWAREVERSION:MSFS 2020".to_string(),
+            "HFFTYFRTYPE:MSFS 2020 GPS Link".to_string(),
+            "HFGPSRECEIVER:MSFS 2020".to_string(),
+            "HFPRSPRESSALTSENSOR:MSFS 2020".to_string(),
+            "HFALGALTGPS:GEO".to_string(),
+            "HFALPALTPRESSURE:ISA".to_string(),
+        ];
+
+        records.map(|record| record + "\r\n").concat()
+    }
+
+    /// The B record, e.g. `B2110305130591N00007085WA0000300101`.
+    fn fix(date: &DateTime<Utc>, data: &GpsData) -> String {
+        let (lat_deg, lat_min, lat_dir) = data.lat_as_degrees_minutes_dir();
+        let (lon_deg, lon_min, lon_dir) = data.lon_as_degrees_minutes_dir();
+
+        format!(
+            "B{}{:02}{:05}{}{:03}{:05}{}A{}{}\r\n",
+            date.format("%H%M%S"),
+            lat_deg as u32,
+            // the minutes are truncated, so the rounding never carries into the degrees
+            (lat_min * 1000.0) as u32,
+            lat_dir,
+            lon_deg as u32,
+            (lon_min * 1000.0) as u32,
+            lon_dir,
+            Self::encode_altitude(data.pressure_altitude),
+            Self::encode_altitude(data.alt),
+        )
+    }
+
+    /// Five characters in meters, with a leading minus sign below zero.
+    fn encode_altitude(meters: f64) -> String {
+        let altitude = meters.round().clamp(-9999.0, 99999.0) as i32;
+
+        if altitude < 0 {
+            format!("-{:04}", -altitude)
+        } else {
+            format!("{altitude:05}")
+        }
+    }
+
+    /// The H records are single lines of printable ASCII.
+    fn sanitize(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| {
+                if c.is_ascii_graphic() || c == ' ' {
+                    c
+                } else {
+                    '?'
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::{Duration, NaiveDate, Utc};
+
+    use crate::recorder::{IgcConfig, RecorderExt};
+
+    use super::Igc;
+
+    #[test]
+    fn test_fix() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: -3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = Igc::fix(&date, &data);
+
+        assert_eq!(result, "B2110305130591N00007085WA-000300101\r\n");
+    }
+
+    #[test]
+    fn test_should_record_fix() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let mut igc = Igc {
+            writer: None,
+            interval: Duration::seconds(4),
+            security_record: false,
+            last_fix: None,
+        };
+
+        assert!(igc.should_record_fix(&date));
+
+        igc.last_fix.replace(date);
+
+        assert!(!igc.should_record_fix(&(date + Duration::seconds(3))));
+        assert!(igc.should_record_fix(&(date + Duration::seconds(4))));
+    }
+
+    #[test]
+    fn test_record() {
+        let directory = std::env::temp_dir().join(format!("msfs-igc-{}", std::process::id()));
+
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let config = IgcConfig {
+            directory: directory.to_string_lossy().to_string(),
+            pilot: "Jane Doe".to_string(),
+            glider_type: "ASK 21".to_string(),
+            glider_id: "D-1234".to_string(),
+            interval_seconds: 1,
+            security_record: true,
+        };
+
+        let mut first = Igc::create(&config, &date).unwrap();
+        let mut second = Igc::create(&config, &date).unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        first.record(data).unwrap();
+        first.finish().unwrap();
+        second.finish().unwrap();
+
+        let result = fs::read_to_string(directory.join("2022-10-30-XXX-MSF-01.igc")).unwrap();
+        let second_result =
+            fs::read_to_string(directory.join("2022-10-30-XXX-MSF-02.igc")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(result.starts_with("AXXXMSFMSFS 2020 GPS Link\r\nHFDTEDATE:301022,01\r\n"));
+        assert!(result.contains("HFPLTPILOTINCHARGE:Jane Doe\r\n"));
+        assert!(result.contains("HFGTYGLIDERTYPE:ASK 21\r\n"));
+        assert!(result.contains("HFGIDGLIDERID:D-1234\r\n"));
+        assert!(result.contains("\r\nB"));
+        assert!(result.ends_with("A0000300101\r\nGMSFS2020GPSLINKUNSIGNED\r\n"));
+        assert!(second_result.contains("HFDTEDATE:301022,02\r\n"));
+    }
+}
@@ -0,0 +1,11 @@
+pub mod config;
+pub mod ext;
+pub mod gpx;
+pub mod igc;
+pub mod telemetry;
+
+pub use config::*;
+pub use ext::*;
+pub use gpx::*;
+pub use igc::*;
+pub use telemetry::*;
@@ -0,0 +1,472 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{error, info, instrument, trace};
+
+use crate::system::messages::RefreshRate;
+use crate::system::simconnect_objects::{Airport, AttitudeData, GpsData, OnGround, SimTime};
+
+use super::{RecorderExt, TelemetryFormat, TelemetryRecorderConfig};
+
+// the union of the data fields of every record type, in the order of the CSV columns
+const TELEMETRY_CSV_DATA_COLUMNS: [&str; 13] = [
+    "lat",
+    "lon",
+    "alt",
+    "pressureAltitude",
+    "gpsGroundTrueTrack",
+    "gpsMagneticVariation",
+    "gpsGroundSpeed",
+    "verticalSpeed",
+    "trueHeading",
+    "pitch",
+    "bank",
+    "simOnGround",
+    "icao",
+];
+
+/// Records every message received from the simulator as it arrived, one line per message,
+/// rotating to a new file once the current one is too large or too old.
+#[derive(Debug)]
+pub struct TelemetryRecorder {
+    // taken once the recording has been completed
+    writer: Option<BufWriter<File>>,
+    directory: PathBuf,
+    format: TelemetryFormat,
+    refresh_rate: RefreshRate,
+    max_file_size: u64,
+    max_file_duration: Duration,
+    session_start: DateTime<Utc>,
+    file_index: u32,
+    file_start: DateTime<Utc>,
+    file_size: u64,
+    sim_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TelemetryRecord<'a> {
+    wall_time: String,
+    sim_time: Option<String>,
+    refresh_rate: RefreshRate,
+    #[serde(flatten)]
+    data: TelemetryData<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data")]
+enum TelemetryData<'a> {
+    GpsData(&'a GpsData),
+    AttitudeData(&'a AttitudeData),
+    OnGround(&'a OnGround),
+    Airport(TelemetryAirport<'a>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TelemetryAirport<'a> {
+    icao: &'a str,
+    lat: f64,
+    lon: f64,
+    alt: f64,
+}
+
+impl TelemetryRecorder {
+    #[instrument(name = "TelemetryRecorder::new")]
+    pub fn new(
+        config: TelemetryRecorderConfig,
+        refresh_rate: RefreshRate,
+    ) -> Result<Box<dyn RecorderExt>, io::Error> {
+        Ok(Box::new(Self::create(&config, refresh_rate, &Utc::now())?))
+    }
+
+    fn create(
+        config: &TelemetryRecorderConfig,
+        refresh_rate: RefreshRate,
+        date: &DateTime<Utc>,
+    ) -> Result<Self, io::Error> {
+        let directory = PathBuf::from(&config.directory);
+
+        fs::create_dir_all(&directory).map_err(|e| {
+            error!(error = ?e, "failed to create the telemetry directory");
+            e
+        })?;
+
+        let mut recorder = TelemetryRecorder {
+            writer: None,
+            directory,
+            format: config.format,
+            refresh_rate,
+            max_file_size: config.max_file_size_mb * 1024 * 1024,
+            max_file_duration: Duration::minutes(config.max_file_duration_minutes as i64),
+            session_start: *date,
+            file_index: 0,
+            file_start: *date,
+            file_size: 0,
+            sim_time: None,
+        };
+        recorder.open(date)?;
+
+        Ok(recorder)
+    }
+
+    fn open(&mut self, date: &DateTime<Utc>) -> Result<(), io::Error> {
+        self.file_index += 1;
+
+        let path = self.path();
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| {
+                error!(error = ?e, path = ?path, "failed to create the telemetry file");
+                e
+            })?;
+
+        let mut writer = BufWriter::new(file);
+        self.file_size = 0;
+        self.file_start = *date;
+
+        if self.format == TelemetryFormat::Csv {
+            let header = Self::csv_header();
+            writer.write_all(header.as_bytes())?;
+            self.file_size += header.len() as u64;
+        }
+        writer.flush()?;
+
+        self.writer = Some(writer);
+
+        info!(path = ?path, "Recording the telemetry");
+
+        Ok(())
+    }
+
+    fn path(&self) -> PathBuf {
+        let extension = match self.format {
+            TelemetryFormat::JsonLines => "jsonl",
+            TelemetryFormat::Csv => "csv",
+        };
+
+        self.directory.join(format!(
+            "{}-{:03}.{}",
+            self.session_start.format("msfs-telemetry-%Y%m%d-%H%M%S"),
+            self.file_index,
+            extension
+        ))
+    }
+
+    fn write(&mut self, data: TelemetryData) -> Result<(), io::Error> {
+        let date = Utc::now();
+
+        if self.should_rotate(&date) {
+            if let Some(mut writer) = self.writer.take() {
+                writer.flush()?;
+            }
+            self.open(&date)?;
+        }
+
+        let line = self.line(&date, data)?;
+
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(line.as_bytes())?;
+            // flushed on every line, so nothing is lost if the app is killed
+            writer.flush()?;
+
+            self.file_size += line.len() as u64;
+
+            trace!("Successfully recorded the telemetry");
+        }
+
+        Ok(())
+    }
+}
+
+impl RecorderExt for TelemetryRecorder {
+    #[instrument(name = "TelemetryRecorder::record", skip(self, data))]
+    fn record(&mut self, data: GpsData) -> Result<(), io::Error> {
+        self.write(TelemetryData::GpsData(&data))
+    }
+
+    #[instrument(name = "TelemetryRecorder::record_attitude", skip(self, data))]
+    fn record_attitude(&mut self, data: AttitudeData) -> Result<(), io::Error> {
+        self.write(TelemetryData::AttitudeData(&data))
+    }
+
+    #[instrument(name = "TelemetryRecorder::record_on_ground", skip(self, data))]
+    fn record_on_ground(&mut self, data: OnGround) -> Result<(), io::Error> {
+        self.write(TelemetryData::OnGround(&data))
+    }
+
+    #[instrument(name = "TelemetryRecorder::record_airports", skip(self, data))]
+    fn record_airports(&mut self, data: Vec<Airport>) -> Result<(), io::Error> {
+        for airport in &data {
+            self.write(TelemetryData::Airport(TelemetryAirport {
+                icao: &airport.icao,
+                lat: airport.lat,
+                lon: airport.lon,
+                alt: airport.alt,
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    fn record_sim_time(&mut self, data: SimTime) -> Result<(), io::Error> {
+        self.sim_time = data.as_date_time();
+
+        Ok(())
+    }
+
+    #[instrument(name = "TelemetryRecorder::finish", skip(self))]
+    fn finish(&mut self) -> Result<(), io::Error> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+
+            info!("Telemetry recording completed");
+        }
+
+        Ok(())
+    }
+}
+
+impl TelemetryRecorder {
+    /// A zero limit never rotates the file.
+    fn should_rotate(&self, date: &DateTime<Utc>) -> bool {
+        let too_large = self.max_file_size > 0 && self.file_size >= self.max_file_size;
+        let too_old = self.max_file_duration > Duration::zero()
+            && *date - self.file_start >= self.max_file_duration;
+
+        too_large || too_old
+    }
+
+    fn line(&self, date: &DateTime<Utc>, data: TelemetryData) -> Result<String, io::Error> {
+        let record = TelemetryRecord {
+            wall_time: date.to_rfc3339_opts(SecondsFormat::Millis, true),
+            sim_time: self
+                .sim_time
+                .map(|sim_time| sim_time.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            refresh_rate: self.refresh_rate,
+            data,
+        };
+
+        match self.format {
+            TelemetryFormat::JsonLines => Ok(serde_json::to_string(&record)? + "\n"),
+            TelemetryFormat::Csv => Ok(Self::csv_line(&serde_json::to_value(&record)?)),
+        }
+    }
+
+    fn csv_header() -> String {
+        ["wallTime", "simTime", "refreshRate", "type"]
+            .iter()
+            .chain(TELEMETRY_CSV_DATA_COLUMNS.iter())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(",")
+            + "\n"
+    }
+
+    /// The fields a record type does not have are left empty.
+    fn csv_line(record: &Value) -> String {
+        ["wallTime", "simTime", "refreshRate", "type"]
+            .iter()
+            .map(|column| &record[column])
+            .chain(
+                TELEMETRY_CSV_DATA_COLUMNS
+                    .iter()
+                    .map(|column| &record["data"][column]),
+            )
+            .map(Self::csv_field)
+            .collect::<Vec<_>>()
+            .join(",")
+            + "\n"
+    }
+
+    fn csv_field(value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(value) if value.contains([',', '"', '\n']) => {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            }
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::{Duration, NaiveDate, Utc};
+
+    use crate::recorder::{RecorderExt, TelemetryFormat, TelemetryRecorderConfig};
+    use crate::system::messages::RefreshRate;
+    use crate::system::simconnect_objects::{AttitudeData, OnGround, SimTime};
+
+    use super::{TelemetryAirport, TelemetryData, TelemetryRecorder};
+
+    fn recorder(format: TelemetryFormat) -> TelemetryRecorder {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        TelemetryRecorder {
+            writer: None,
+            directory: std::env::temp_dir(),
+            format,
+            refresh_rate: RefreshRate::Fast,
+            max_file_size: 1024,
+            max_file_duration: Duration::minutes(60),
+            session_start: date,
+            file_index: 1,
+            file_start: date,
+            file_size: 0,
+            sim_time: None,
+        }
+    }
+
+    #[test]
+    fn test_json_line() {
+        let mut recorder = recorder(TelemetryFormat::JsonLines);
+        recorder.sim_time = SimTime {
+            absolute_time: 63_802_761_030.5,
+        }
+        .as_date_time();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let result = recorder
+            .line(&recorder.file_start, TelemetryData::GpsData(&data))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "{\"wallTime\":\"2022-10-30T21:10:30.750Z\",\"simTime\":\"2022-10-30T21:10:30.500Z\",\"refreshRate\":\"fast\",\
+             \"type\":\"GpsData\",\"data\":{\"lat\":51.509865,\"lon\":-0.118092,\"alt\":100.5,\"pressureAltitude\":3.0,\
+             \"gpsGroundTrueTrack\":310.55,\"gpsMagneticVariation\":5.0,\"gpsGroundSpeed\":100.5,\"verticalSpeed\":0.0}}\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_line() {
+        let recorder = recorder(TelemetryFormat::Csv);
+
+        let data = AttitudeData {
+            true_heading: 310.5,
+            pitch: -2.5,
+            bank: 10.0,
+        };
+
+        let result = recorder
+            .line(&recorder.file_start, TelemetryData::AttitudeData(&data))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "2022-10-30T21:10:30.750Z,,fast,AttitudeData,,,,,,,,,310.5,-2.5,10.0,,\n"
+        );
+
+        let result = recorder
+            .line(
+                &recorder.file_start,
+                TelemetryData::Airport(TelemetryAirport {
+                    icao: "EG,LL",
+                    lat: 51.4775,
+                    lon: -0.461389,
+                    alt: 25.0,
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "2022-10-30T21:10:30.750Z,,fast,Airport,51.4775,-0.461389,25.0,,,,,,,,,,\"EG,LL\"\n"
+        );
+        assert_eq!(
+            TelemetryRecorder::csv_header().split(',').count(),
+            result.split(',').count() - 1
+        );
+    }
+
+    #[test]
+    fn test_should_rotate() {
+        let mut recorder = recorder(TelemetryFormat::JsonLines);
+        let date = recorder.file_start;
+
+        assert!(!recorder.should_rotate(&date));
+        assert!(!recorder.should_rotate(&(date + Duration::minutes(59))));
+        assert!(recorder.should_rotate(&(date + Duration::minutes(60))));
+
+        recorder.file_size = 1024;
+        assert!(recorder.should_rotate(&date));
+
+        recorder.max_file_size = 0;
+        recorder.max_file_duration = Duration::zero();
+        assert!(!recorder.should_rotate(&(date + Duration::days(1))));
+    }
+
+    #[test]
+    fn test_record() {
+        let directory = std::env::temp_dir().join(format!("msfs-telemetry-{}", std::process::id()));
+
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let config = TelemetryRecorderConfig {
+            directory: directory.to_string_lossy().to_string(),
+            format: TelemetryFormat::Csv,
+            max_file_size_mb: 1,
+            // the files are dated in the past, so only the size rotates them
+            max_file_duration_minutes: 0,
+        };
+
+        let mut recorder = TelemetryRecorder::create(&config, RefreshRate::Slow, &date).unwrap();
+
+        recorder
+            .record_on_ground(OnGround {
+                sim_on_ground: true,
+            })
+            .unwrap();
+
+        // a full file rotates before the next line
+        recorder.file_size = recorder.max_file_size;
+        recorder
+            .record_on_ground(OnGround {
+                sim_on_ground: false,
+            })
+            .unwrap();
+        recorder.finish().unwrap();
+
+        let first =
+            fs::read_to_string(directory.join("msfs-telemetry-20221030-211030-001.csv")).unwrap();
+        let second =
+            fs::read_to_string(directory.join("msfs-telemetry-20221030-211030-002.csv")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(first.starts_with("wallTime,simTime,refreshRate,type,lat,"));
+        assert_eq!(first.lines().count(), 2);
+        assert!(first.ends_with(",slow,OnGround,,,,,,,,,,,,true,\n"));
+        assert!(second.starts_with("wallTime,simTime,refreshRate,type,lat,"));
+        assert!(second.ends_with(",slow,OnGround,,,,,,,,,,,,false,\n"));
+    }
+}
@@ -0,0 +1,220 @@
+use std::io;
+
+use chrono::{DateTime, Utc};
+use quick_xml::{events::Event, Reader};
+
+use crate::system::simconnect_objects::GpsData;
+
+use super::ReplayData;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+#[derive(Debug, Default)]
+struct TrackPoint {
+    lat: f64,
+    lon: f64,
+    ele: Option<f64>,
+    time: Option<DateTime<Utc>>,
+    speed: Option<f64>,
+    course: Option<f64>,
+}
+
+/// Reads the track points of a GPX file. The speed, course and vertical speed are taken from the
+/// previous point when the file does not have them.
+pub fn parse(contents: &str) -> Result<Vec<(DateTime<Utc>, ReplayData)>, io::Error> {
+    let points = parse_track_points(contents)?;
+
+    let mut records = Vec::with_capacity(points.len());
+    let mut previous: Option<&TrackPoint> = None;
+
+    for (index, point) in points.iter().enumerate() {
+        let time = point.time.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("track point {} has no time", index + 1),
+            )
+        })?;
+        let alt = point.ele.unwrap_or_default();
+
+        // the first point does not have a previous one, so its course is towards the next point
+        let course = match (previous, points.get(index + 1)) {
+            (Some(previous), _) => bearing(previous, point),
+            (None, Some(next)) => bearing(point, next),
+            (None, None) => 0.0,
+        };
+
+        let (speed, vertical_speed) = match previous.and_then(|previous| {
+            let seconds = (time - previous.time?).num_milliseconds() as f64 / 1000.0;
+            (seconds > 0.0).then_some((previous, seconds))
+        }) {
+            Some((previous, seconds)) => (
+                distance(previous, point) / seconds,
+                (alt - previous.ele.unwrap_or_default()) / seconds,
+            ),
+            None => (0.0, 0.0),
+        };
+
+        records.push((
+            time,
+            ReplayData::Gps(GpsData {
+                lat: point.lat,
+                lon: point.lon,
+                alt,
+                pressure_altitude: alt,
+                gps_ground_true_track: point.course.unwrap_or(course),
+                gps_magnetic_variation: 0.0,
+                gps_ground_speed: point.speed.unwrap_or(speed),
+                vertical_speed,
+            }),
+        ));
+
+        previous = Some(point);
+    }
+
+    Ok(records)
+}
+
+fn parse_track_points(contents: &str) -> Result<Vec<TrackPoint>, io::Error> {
+    let mut reader = Reader::from_str(contents);
+    reader.trim_text(true);
+
+    let mut points = Vec::new();
+    let mut point: Option<TrackPoint> = None;
+    let mut element = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(invalid_data)? {
+            Event::Start(e) if e.local_name().as_ref() == b"trkpt" => {
+                let mut track_point = TrackPoint::default();
+
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(invalid_data)?;
+                    let value = attribute.unescape_value().map_err(invalid_data)?;
+
+                    match attribute.key.local_name().as_ref() {
+                        b"lat" => track_point.lat = value.parse().map_err(invalid_data)?,
+                        b"lon" => track_point.lon = value.parse().map_err(invalid_data)?,
+                        _ => (),
+                    }
+                }
+
+                point = Some(track_point);
+            }
+            Event::Start(e) => element = e.local_name().as_ref().to_vec(),
+            Event::Text(e) => {
+                if let Some(point) = point.as_mut() {
+                    let text = e.unescape().map_err(invalid_data)?;
+
+                    match element.as_slice() {
+                        b"ele" => point.ele = Some(text.parse().map_err(invalid_data)?),
+                        b"time" => {
+                            point.time = Some(
+                                DateTime::parse_from_rfc3339(&text)
+                                    .map_err(invalid_data)?
+                                    .with_timezone(&Utc),
+                            )
+                        }
+                        b"speed" => point.speed = Some(text.parse().map_err(invalid_data)?),
+                        b"course" => point.course = Some(text.parse().map_err(invalid_data)?),
+                        _ => (),
+                    }
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"trkpt" => {
+                points.extend(point.take());
+            }
+            Event::End(_) => element.clear(),
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(points)
+}
+
+/// The initial great-circle bearing, in degrees from true north.
+fn bearing(from: &TrackPoint, to: &TrackPoint) -> f64 {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let delta_lon = (to.lon - from.lon).to_radians();
+
+    let y = delta_lon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// The haversine distance, in meters.
+fn distance(from: &TrackPoint, to: &TrackPoint) -> f64 {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let delta_lat = lat2 - lat1;
+    let delta_lon = (to.lon - from.lon).to_radians();
+
+    let a =
+        (delta_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+fn invalid_data<E>(e: E) -> io::Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::replay::ReplayData;
+
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let contents = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                        <gpx version=\"1.1\" creator=\"MSFS 2020 GPS Link\" xmlns=\"http://www.topografix.com/GPX/1/1\" \
+                        xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v2\">\n\
+                        <metadata>\n<time>2022-10-30T21:10:30Z</time>\n</metadata>\n\
+                        <trk>\n<name>MSFS 2022-10-30 21:10</name>\n<trkseg>\n\
+                        <trkpt lat=\"51.5098650\" lon=\"-0.1180920\"><ele>100.5</ele><time>2022-10-30T21:10:30.750Z</time>\
+                        <extensions><gpxtpx:TrackPointExtension><gpxtpx:speed>100.50</gpxtpx:speed><gpxtpx:course>310.6</gpxtpx:course>\
+                        </gpxtpx:TrackPointExtension></extensions></trkpt>\n\
+                        <trkpt lat=\"51.5107643\" lon=\"-0.1180920\"><ele>110.5</ele><time>2022-10-30T21:10:31.750Z</time></trkpt>\n\
+                        </trkseg>\n</trk>\n</gpx>\n";
+
+        let result = parse(contents).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0.timestamp_millis(), 1667164230750);
+
+        match &result[0].1 {
+            ReplayData::Gps(data) => {
+                assert_eq!(data.lat, 51.509865);
+                assert_eq!(data.alt, 100.5);
+                assert_eq!(data.gps_ground_speed, 100.5);
+                assert_eq!(data.gps_ground_true_track, 310.6);
+                assert_eq!(data.vertical_speed, 0.0);
+            }
+            _ => panic!("expected a position"),
+        }
+
+        // the second point has no extensions, so they are derived from the first one
+        match &result[1].1 {
+            ReplayData::Gps(data) => {
+                assert_eq!(data.gps_ground_speed.round(), 100.0);
+                assert_eq!(data.gps_ground_true_track.round(), 0.0);
+                assert_eq!(data.vertical_speed, 10.0);
+            }
+            _ => panic!("expected a position"),
+        }
+    }
+
+    #[test]
+    fn test_parse_without_time() {
+        let contents = "<gpx><trk><trkseg><trkpt lat=\"51.5\" lon=\"-0.1\"><ele>10</ele></trkpt></trkseg></trk></gpx>";
+
+        assert_eq!(
+            parse(contents).unwrap_err().to_string(),
+            "track point 1 has no time"
+        );
+    }
+}
@@ -0,0 +1,8 @@
+pub mod gpx;
+pub mod nmea;
+pub mod playback;
+pub mod session;
+pub mod telemetry;
+
+pub use playback::*;
+pub use session::*;
@@ -0,0 +1,46 @@
+use std::io;
+
+use chrono::{DateTime, Utc};
+
+use crate::input::NmeaParser;
+
+use super::ReplayData;
+
+/// Reads an NMEA 0183 capture. The lines that are not GGA, RMC, VTG or PGRMZ are skipped.
+pub fn parse(contents: &str) -> Result<Vec<(DateTime<Utc>, ReplayData)>, io::Error> {
+    let mut parser = NmeaParser::default();
+
+    let mut fixes = contents
+        .lines()
+        .flat_map(|line| parser.parse_line(line))
+        .collect::<Vec<_>>();
+    fixes.extend(parser.finish());
+
+    Ok(fixes
+        .into_iter()
+        .map(|(date, data)| (date, ReplayData::Gps(data)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::replay::ReplaySession;
+
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let contents = "$GPGGA,211030.750,5130.5919,N,00007.0855,W,1,12,1.0,3.0,M,0.0,M,,*70\r\n\
+                        $GPRMC,211030.750,A,5130.5919,N,00007.0855,W,195.36,310.55,301022,5.0,E,S*07\r\n\
+                        $GPGSV,1,1,00*79\r\n\
+                        $GPGGA,211031.750,5130.5919,N,00007.0855,W,1,12,1.0,5.0,M,0.0,M,,*77\r\n";
+
+        let result = parse(contents).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let session = ReplaySession::from_records(result).unwrap();
+        assert_eq!(session.duration(), Duration::from_secs(1));
+    }
+}
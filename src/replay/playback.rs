@@ -0,0 +1,147 @@
+use std::{ops::Range, time::Duration};
+
+use super::ReplayFrame;
+
+pub const REPLAY_MIN_SPEED: f64 = 0.5;
+pub const REPLAY_MAX_SPEED: f64 = 16.0;
+
+/// The position of the replay in the session, which only moves forward while it is not paused.
+#[derive(Debug)]
+pub struct ReplayPlayback {
+    position: Duration,
+    speed: f64,
+    paused: bool,
+    next_frame: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(speed: f64) -> Self {
+        Self {
+            position: Duration::ZERO,
+            speed: Self::clamp_speed(speed),
+            paused: false,
+            next_frame: 0,
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = Self::clamp_speed(speed);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// The frames from the new position on are sent as the replay moves forward again.
+    pub fn seek(&mut self, position: Duration, frames: &[ReplayFrame]) {
+        let end = frames.last().map(|frame| frame.offset).unwrap_or_default();
+
+        self.position = position.min(end);
+        self.next_frame = frames.partition_point(|frame| frame.offset < self.position);
+    }
+
+    /// Moves forward by the wall-clock time since the last call and returns the frames that are now due.
+    pub fn advance(&mut self, elapsed: Duration, frames: &[ReplayFrame]) -> Range<usize> {
+        if !self.paused {
+            self.position += elapsed.mul_f64(self.speed);
+        }
+
+        let start = self.next_frame.min(frames.len());
+        self.next_frame =
+            start + frames[start..].partition_point(|frame| frame.offset <= self.position);
+
+        start..self.next_frame
+    }
+
+    pub fn is_finished(&self, frames: &[ReplayFrame]) -> bool {
+        self.next_frame >= frames.len()
+    }
+
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    fn clamp_speed(speed: f64) -> f64 {
+        if speed.is_finite() {
+            speed.clamp(REPLAY_MIN_SPEED, REPLAY_MAX_SPEED)
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::replay::{ReplayData, ReplayFrame};
+    use crate::system::simconnect_objects::AttitudeData;
+
+    use super::ReplayPlayback;
+
+    fn frames() -> Vec<ReplayFrame> {
+        (0..5)
+            .map(|i| ReplayFrame {
+                offset: Duration::from_secs(i),
+                data: ReplayData::Attitude(AttitudeData {
+                    true_heading: 0.0,
+                    pitch: 0.0,
+                    bank: 0.0,
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_advance() {
+        let frames = frames();
+        let mut playback = ReplayPlayback::new(1.0);
+
+        assert_eq!(playback.advance(Duration::ZERO, &frames), 0..1);
+        assert_eq!(playback.advance(Duration::from_millis(500), &frames), 1..1);
+        assert_eq!(playback.advance(Duration::from_millis(1500), &frames), 1..3);
+
+        playback.set_paused(true);
+        assert_eq!(playback.advance(Duration::from_secs(10), &frames), 3..3);
+
+        playback.set_paused(false);
+        playback.set_speed(2.0);
+        assert_eq!(playback.advance(Duration::from_secs(1), &frames), 3..5);
+        assert!(playback.is_finished(&frames));
+        assert_eq!(playback.advance(Duration::from_secs(1), &frames), 5..5);
+    }
+
+    #[test]
+    fn test_seek() {
+        let frames = frames();
+        let mut playback = ReplayPlayback::new(1.0);
+
+        playback.seek(Duration::from_millis(2500), &frames);
+        assert_eq!(playback.advance(Duration::from_millis(500), &frames), 3..4);
+
+        // seeking back sends the frames again
+        playback.seek(Duration::from_secs(1), &frames);
+        assert_eq!(playback.advance(Duration::ZERO, &frames), 1..2);
+
+        playback.seek(Duration::from_secs(60), &frames);
+        assert_eq!(playback.position(), Duration::from_secs(4));
+        assert_eq!(playback.advance(Duration::ZERO, &frames), 4..5);
+    }
+
+    #[test]
+    fn test_speed() {
+        let frames = frames();
+
+        let mut playback = ReplayPlayback::new(100.0);
+        playback.advance(Duration::from_secs(1), &frames);
+        assert_eq!(playback.position(), Duration::from_secs(16));
+
+        playback = ReplayPlayback::new(0.1);
+        playback.advance(Duration::from_secs(1), &frames);
+        assert_eq!(playback.position(), Duration::from_millis(500));
+
+        playback.set_speed(f64::NAN);
+        playback.advance(Duration::from_secs(1), &frames);
+        assert_eq!(playback.position(), Duration::from_millis(1500));
+    }
+}
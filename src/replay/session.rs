@@ -0,0 +1,172 @@
+use std::{ffi::OsStr, fs, io, path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tracing::{error, info, instrument};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::{gpx, nmea, telemetry};
+
+#[derive(Debug, Clone)]
+pub enum ReplayData {
+    Gps(GpsData),
+    Attitude(AttitudeData),
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    // from the first frame of the session
+    pub offset: Duration,
+    pub data: ReplayData,
+}
+
+/// A recorded session, with its frames in the order they were received.
+#[derive(Debug, Clone)]
+pub struct ReplaySession {
+    start: DateTime<Utc>,
+    frames: Vec<ReplayFrame>,
+}
+
+impl ReplaySession {
+    /// Reads a telemetry recording (`.jsonl` or `.csv`), a GPX track (`.gpx`)
+    /// or an NMEA 0183 capture (`.nmea`, `.log` or `.txt`).
+    #[instrument(name = "ReplaySession::load")]
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            error!(error = ?e, "failed to read the replay file");
+            e
+        })?;
+
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase);
+
+        let records = match extension.as_deref() {
+            Some("jsonl") => telemetry::parse_json_lines(&contents),
+            Some("csv") => telemetry::parse_csv(&contents),
+            Some("gpx") => gpx::parse(&contents),
+            Some("nmea" | "log" | "txt") => nmea::parse(&contents),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the replay file must be a telemetry recording, a GPX track or an NMEA capture",
+            )),
+        }
+        .map_err(|e| {
+            error!(error = ?e, "failed to parse the replay file");
+            e
+        })?;
+
+        let session = Self::from_records(records)?;
+
+        info!(
+            frames = session.frames.len(),
+            duration = ?session.duration(),
+            "Loaded the replay session"
+        );
+
+        Ok(session)
+    }
+
+    pub fn from_records(mut records: Vec<(DateTime<Utc>, ReplayData)>) -> Result<Self, io::Error> {
+        if !records
+            .iter()
+            .any(|(_, data)| matches!(data, ReplayData::Gps(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the replay file does not contain any position",
+            ));
+        }
+
+        // the sort is stable, so records with the same time keep the order they were received in
+        records.sort_by_key(|(date, _)| *date);
+
+        let start = records[0].0;
+        let frames = records
+            .into_iter()
+            .map(|(date, data)| ReplayFrame {
+                offset: (date - start).to_std().unwrap_or_default(),
+                data,
+            })
+            .collect();
+
+        Ok(Self { start, frames })
+    }
+
+    /// The time the frame was recorded at.
+    pub fn date(&self, frame: &ReplayFrame) -> DateTime<Utc> {
+        self.start
+            + chrono::Duration::from_std(frame.offset).unwrap_or_else(|_| chrono::Duration::zero())
+    }
+
+    pub fn frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frames
+            .last()
+            .map(|frame| frame.offset)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::system::simconnect_objects::AttitudeData;
+
+    use super::{ReplayData, ReplaySession};
+
+    #[test]
+    fn test_from_records() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .and_then(|d| d.and_hms_milli_opt(21, 10, 30, 750))
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
+
+        let data = crate::system::simconnect_objects::GpsData {
+            lat: 51.509865,
+            lon: -0.118092,
+            alt: 100.5,
+            pressure_altitude: 3.0,
+            gps_ground_true_track: 310.55,
+            gps_magnetic_variation: 5.0,
+            gps_ground_speed: 100.50,
+            vertical_speed: 0.0,
+        };
+
+        let attitude = AttitudeData {
+            true_heading: 310.5,
+            pitch: -2.5,
+            bank: 10.0,
+        };
+
+        let session = ReplaySession::from_records(vec![
+            (
+                date + chrono::Duration::milliseconds(1500),
+                ReplayData::Gps(data.clone()),
+            ),
+            (date, ReplayData::Attitude(attitude.clone())),
+            (date, ReplayData::Gps(data)),
+        ])
+        .unwrap();
+
+        let frames = session.frames();
+
+        assert_eq!(frames.len(), 3);
+        assert!(matches!(frames[0].data, ReplayData::Attitude(_)));
+        assert!(matches!(frames[1].data, ReplayData::Gps(_)));
+        assert_eq!(frames[1].offset, Duration::ZERO);
+        assert_eq!(session.duration(), Duration::from_millis(1500));
+
+        let result = ReplaySession::from_records(vec![(date, ReplayData::Attitude(attitude))]);
+
+        assert!(result.is_err());
+    }
+}
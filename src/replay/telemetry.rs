@@ -0,0 +1,192 @@
+use std::io;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
+
+use super::ReplayData;
+
+/// Reads the JSON Lines written by the telemetry recorder. Only the positions and attitudes are kept.
+pub fn parse_json_lines(contents: &str) -> Result<Vec<(DateTime<Utc>, ReplayData)>, io::Error> {
+    let mut records = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str::<Value>(line)
+            .map_err(io::Error::from)
+            .and_then(|record| parse_record(&record))
+            .map_err(|e| invalid_line(index, e))?;
+
+        records.extend(record);
+    }
+
+    Ok(records)
+}
+
+/// Reads the CSV written by the telemetry recorder, which has the same fields as its JSON Lines.
+pub fn parse_csv(contents: &str) -> Result<Vec<(DateTime<Utc>, ReplayData)>, io::Error> {
+    let mut lines = contents.lines().enumerate();
+
+    let columns = match lines.next() {
+        Some((_, header)) => split_csv_line(header),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut records = Vec::new();
+
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut record = Map::new();
+        let mut data = Map::new();
+
+        for (column, field) in columns.iter().zip(split_csv_line(line)) {
+            if field.is_empty() {
+                continue;
+            }
+
+            match column.as_str() {
+                "wallTime" | "simTime" | "refreshRate" | "type" => {
+                    record.insert(column.clone(), Value::String(field));
+                }
+                _ => {
+                    data.insert(column.clone(), csv_value(field));
+                }
+            }
+        }
+
+        record.insert("data".to_string(), Value::Object(data));
+
+        let record = parse_record(&Value::Object(record)).map_err(|e| invalid_line(index, e))?;
+
+        records.extend(record);
+    }
+
+    Ok(records)
+}
+
+fn parse_record(record: &Value) -> Result<Option<(DateTime<Utc>, ReplayData)>, io::Error> {
+    let wall_time = record["wallTime"]
+        .as_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the wall time is missing"))?;
+    let wall_time = DateTime::parse_from_rfc3339(wall_time)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        .with_timezone(&Utc);
+
+    let data = match record["type"].as_str() {
+        Some("GpsData") => {
+            ReplayData::Gps(serde_json::from_value::<GpsData>(record["data"].clone())?)
+        }
+        Some("AttitudeData") => ReplayData::Attitude(serde_json::from_value::<AttitudeData>(
+            record["data"].clone(),
+        )?),
+        // the other messages do not move the aircraft
+        _ => return Ok(None),
+    };
+
+    Ok(Some((wall_time, data)))
+}
+
+fn csv_value(field: String) -> Value {
+    if let Ok(value) = field.parse::<bool>() {
+        return Value::Bool(value);
+    }
+
+    match field.parse::<f64>() {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::String(field),
+    }
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+fn invalid_line(index: usize, e: io::Error) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", index + 1, e),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::replay::ReplayData;
+
+    use super::{parse_csv, parse_json_lines, split_csv_line};
+
+    #[test]
+    fn test_parse_json_lines() {
+        let contents = "{\"wallTime\":\"2022-10-30T21:10:30.750Z\",\"simTime\":null,\"refreshRate\":\"fast\",\
+                        \"type\":\"GpsData\",\"data\":{\"lat\":51.509865,\"lon\":-0.118092,\"alt\":100.5,\"pressureAltitude\":3.0,\
+                        \"gpsGroundTrueTrack\":310.55,\"gpsMagneticVariation\":5.0,\"gpsGroundSpeed\":100.5,\"verticalSpeed\":0.0}}\n\
+                        {\"wallTime\":\"2022-10-30T21:10:31.000Z\",\"simTime\":null,\"refreshRate\":\"fast\",\
+                        \"type\":\"OnGround\",\"data\":{\"simOnGround\":true}}\n\
+                        \n\
+                        {\"wallTime\":\"2022-10-30T21:10:31.250Z\",\"simTime\":null,\"refreshRate\":\"fast\",\
+                        \"type\":\"AttitudeData\",\"data\":{\"trueHeading\":310.5,\"pitch\":-2.5,\"bank\":10.0}}\n";
+
+        let result = parse_json_lines(contents).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0.timestamp_millis(), 1667164230750);
+        assert!(matches!(&result[0].1, ReplayData::Gps(data) if data.gps_ground_speed == 100.5));
+        assert!(matches!(&result[1].1, ReplayData::Attitude(data) if data.bank == 10.0));
+
+        let result = parse_json_lines(
+            "{\"wallTime\":\"2022-10-30T21:10:30.750Z\",\"type\":\"GpsData\",\"data\":{}}\n",
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "line 1: missing field `lat`"
+        );
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let contents = "wallTime,simTime,refreshRate,type,lat,lon,alt,pressureAltitude,gpsGroundTrueTrack,\
+                        gpsMagneticVariation,gpsGroundSpeed,verticalSpeed,trueHeading,pitch,bank,simOnGround,icao\n\
+                        2022-10-30T21:10:30.750Z,,slow,GpsData,51.509865,-0.118092,100.5,3.0,310.55,5.0,100.5,0.0,,,,,\n\
+                        2022-10-30T21:10:31.000Z,,slow,Airport,51.4775,-0.461389,25.0,,,,,,,,,,\"EG,LL\"\n\
+                        2022-10-30T21:10:31.250Z,,slow,AttitudeData,,,,,,,,,310.5,-2.5,10.0,,\n";
+
+        let result = parse_csv(contents).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(matches!(&result[0].1, ReplayData::Gps(data) if data.lon == -0.118092));
+        assert!(matches!(&result[1].1, ReplayData::Attitude(data) if data.pitch == -2.5));
+    }
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(
+            split_csv_line("a,,\"b,\"\"c\"\"\",d"),
+            vec!["a", "", "b,\"c\"", "d"]
+        );
+    }
+}
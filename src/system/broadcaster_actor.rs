@@ -3,9 +3,12 @@ use opentelemetry_api::Context as OpenTelemetryContext;
 use tracing::{debug, info, instrument, warn, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-use crate::broadcaster::{BroadcasterConfig, BroadcasterExt, Com, Udp};
+use crate::broadcaster::{
+    BroadcasterConfig, BroadcasterExt, Com, Cot, Gdl90, Gpsd, Kml, Mqtt, TcpServer, Udp, WebSocket,
+    XPlaneData,
+};
 use crate::system::messages::{SimConnectDataMessage, StopMessage};
-use crate::system::simconnect_objects::GpsData;
+use crate::system::simconnect_objects::{AttitudeData, GpsData};
 
 #[derive(Debug)]
 pub struct BroadcasterActor {
@@ -36,6 +39,14 @@ impl Actor for BroadcasterActor {
         let broadcaster = match self.config.clone() {
             BroadcasterConfig::Udp(config) => Udp::new(config),
             BroadcasterConfig::Com(config) => Com::new(config),
+            BroadcasterConfig::Gdl90(config) => Gdl90::new(config),
+            BroadcasterConfig::TcpServer(config) => TcpServer::new(config),
+            BroadcasterConfig::Gpsd(config) => Gpsd::new(config),
+            BroadcasterConfig::WebSocket(config) => WebSocket::new(config),
+            BroadcasterConfig::Mqtt(config) => Mqtt::new(config),
+            BroadcasterConfig::Cot(config) => Cot::new(config),
+            BroadcasterConfig::XPlaneData(config) => XPlaneData::new(config),
+            BroadcasterConfig::Kml(config) => Kml::new(config),
         };
 
         match broadcaster {
@@ -94,6 +105,40 @@ impl Handler<SimConnectDataMessage<GpsData>> for BroadcasterActor {
     }
 }
 
+impl Handler<SimConnectDataMessage<AttitudeData>> for BroadcasterActor {
+    type Result = ();
+
+    #[instrument(
+        name = "BroadcasterActor::handle::<SimConnectDataMessage<AttitudeData>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<AttitudeData>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(broadcaster) = self.broadcaster.as_mut() {
+            debug!("Broadcasting SimConnectDataMessage<AttitudeData> message");
+
+            let result = broadcaster.send_attitude(data);
+
+            if result.is_err() {
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to send broadcast".to_string(),
+                })
+                .expect("BroadcasterActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current broadcaster");
+        }
+    }
+}
+
 impl Handler<StopMessage> for BroadcasterActor {
     type Result = ();
 
@@ -5,21 +5,28 @@ use tokio::sync::mpsc::Receiver;
 use tracing::{debug, error, field, info, instrument, warn, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use crate::broadcaster::BroadcasterConfig;
 use crate::cmd::ChannelResponse;
+use crate::input::SourceOptions;
 use crate::system::{
     broadcaster_actor::BroadcasterActor,
     landing_detection_actor::LandingDetectionActor,
-    messages::{CoordinatorMessage, GetStatusMessage, GetStatusResponseMessage, StopMessage},
-    simconnect_actor::SimConnectActor,
+    messages::{
+        BroadcasterStatus, CoordinatorMessage, GetStatusMessage, GetStatusResponseMessage,
+        ReplayControl, ReplayControlMessage, StopMessage,
+    },
+    recorder_actor::RecorderActor,
+    source_actor::SourceActor,
 };
 
 #[derive(Debug)]
 pub struct CoordinatorActor {
     rx: Option<Receiver<CoordinatorMessage>>,
     handle: Option<SpawnHandle>,
-    broadcaster_addr: Option<Addr<BroadcasterActor>>,
+    broadcaster_addrs: Vec<(&'static str, Addr<BroadcasterActor>)>,
+    recorder_addrs: Vec<Addr<RecorderActor>>,
     landing_detection_addr: Option<Addr<LandingDetectionActor>>,
-    simconnect_addr: Option<Addr<SimConnectActor>>,
+    source_addr: Option<Addr<SourceActor>>,
 }
 
 impl CoordinatorActor {
@@ -27,9 +34,10 @@ impl CoordinatorActor {
         Self {
             rx: Some(rx),
             handle: None,
-            broadcaster_addr: None,
+            broadcaster_addrs: Vec::new(),
+            recorder_addrs: Vec::new(),
             landing_detection_addr: None,
-            simconnect_addr: None,
+            source_addr: None,
         }
     }
 }
@@ -129,7 +137,9 @@ impl Handler<CoordinatorMessage> for CoordinatorActor {
             CoordinatorMessage::Start {
                 context,
                 refresh_rate,
-                config,
+                source,
+                configs,
+                recorders,
             } => {
                 span.set_parent(context);
                 debug!("CoordinatorActor received Start");
@@ -141,24 +151,46 @@ impl Handler<CoordinatorMessage> for CoordinatorActor {
 
                 let coordinator_addr = ctx.address();
 
-                let broadcaster_addr = BroadcasterActor::new(span.context(), config).start();
+                let attitude_rate_hz = configs
+                    .iter()
+                    .filter_map(BroadcasterConfig::attitude_rate_hz)
+                    .max();
+
+                let broadcaster_addrs = Self::start_broadcasters(&span, configs);
+
+                let recorder_addrs = recorders
+                    .into_iter()
+                    .map(|config| RecorderActor::new(span.context(), config, refresh_rate).start())
+                    .collect::<Vec<_>>();
 
                 let landing_detection_addr = LandingDetectionActor::new(span.context()).start();
 
-                let simconnect = SimConnectActor::new(
-                    span.context(),
+                let options = SourceOptions {
                     refresh_rate,
                     // disabled for now as this functionality is not fully implemented
-                    false,
+                    landing_detection_enabled: false,
+                    recording: !recorder_addrs.is_empty(),
+                    attitude_rate_hz,
+                };
+
+                let source = SourceActor::new(
+                    span.context(),
+                    source,
+                    options,
                     coordinator_addr,
-                    broadcaster_addr.clone(),
+                    broadcaster_addrs
+                        .iter()
+                        .map(|(_, addr)| addr.clone())
+                        .collect(),
+                    recorder_addrs.clone(),
                     landing_detection_addr.clone(),
                 )
                 .start();
 
-                self.broadcaster_addr = Some(broadcaster_addr);
+                self.broadcaster_addrs = broadcaster_addrs;
+                self.recorder_addrs = recorder_addrs;
                 self.landing_detection_addr = Some(landing_detection_addr);
-                self.simconnect_addr = Some(simconnect);
+                self.source_addr = Some(source);
             }
             CoordinatorMessage::Stop { context } => {
                 span.set_parent(context);
@@ -178,10 +210,13 @@ impl Handler<CoordinatorMessage> for CoordinatorActor {
 
                 let mut successful_checks = 0u32;
 
-                if let Some(addr) = &self.broadcaster_addr {
-                    if addr.connected() {
-                        successful_checks += 1;
-                    }
+                // the session is healthy as long as at least one broadcaster is running
+                if self
+                    .broadcaster_addrs
+                    .iter()
+                    .any(|(_, addr)| addr.connected())
+                {
+                    successful_checks += 1;
                 }
 
                 if let Some(addr) = &self.landing_detection_addr {
@@ -190,15 +225,15 @@ impl Handler<CoordinatorMessage> for CoordinatorActor {
                     }
                 }
 
-                if let Some(addr) = &self.simconnect_addr {
+                if let Some(addr) = &self.source_addr {
                     if addr.connected() {
                         successful_checks += 1;
                     }
                 }
 
                 if successful_checks == 3 {
-                    // things might be OK but we must check if the SimConnect actor is connected
-                    self.simconnect_addr
+                    // things might be OK but we must check if the source is still sending
+                    self.source_addr
                         .as_ref()
                         .expect("this should never happen")
                         // it's fine not to check the result here
@@ -219,11 +254,84 @@ impl Handler<CoordinatorMessage> for CoordinatorActor {
                     });
                 }
             }
+            CoordinatorMessage::BroadcastersStatus {
+                context,
+                response_channel,
+            } => {
+                span.set_parent(context);
+                debug!("CoordinatorActor received BroadcastersStatus");
+
+                let data = self
+                    .broadcaster_addrs
+                    .iter()
+                    .map(|(broadcaster_type, addr)| BroadcasterStatus {
+                        broadcaster_type,
+                        running: addr.connected(),
+                    })
+                    .collect::<Vec<_>>();
+
+                let response = ChannelResponse {
+                    context: Span::current().context(),
+                    data,
+                };
+
+                if let Err(e) = response_channel.send(response) {
+                    error!(error = ?e, "failed to send through the oneshot channel");
+                }
+            }
+            CoordinatorMessage::PauseReplay { context, paused } => {
+                span.set_parent(context);
+                debug!("CoordinatorActor received PauseReplay");
+
+                self.control_replay(ReplayControl::Pause(paused));
+            }
+            CoordinatorMessage::SeekReplay { context, position } => {
+                span.set_parent(context);
+                debug!("CoordinatorActor received SeekReplay");
+
+                self.control_replay(ReplayControl::Seek(position));
+            }
+            CoordinatorMessage::SetReplaySpeed { context, speed } => {
+                span.set_parent(context);
+                debug!("CoordinatorActor received SetReplaySpeed");
+
+                self.control_replay(ReplayControl::Speed(speed));
+            }
         }
     }
 }
 
 impl CoordinatorActor {
+    /// Every broadcaster runs in its own actor, so a failing one does not stop the others.
+    fn start_broadcasters(
+        span: &Span,
+        configs: Vec<BroadcasterConfig>,
+    ) -> Vec<(&'static str, Addr<BroadcasterActor>)> {
+        configs
+            .into_iter()
+            .map(|config| {
+                (
+                    config.broadcaster_type(),
+                    BroadcasterActor::new(span.context(), config).start(),
+                )
+            })
+            .collect()
+    }
+
+    fn control_replay(&self, control: ReplayControl) {
+        match &self.source_addr {
+            Some(addr) if addr.connected() => {
+                // it's fine not to check the result here
+                // because the source is either running or stopping
+                addr.do_send(ReplayControlMessage {
+                    context: Span::current().context(),
+                    control,
+                });
+            }
+            _ => warn!("there is no data source running"),
+        }
+    }
+
     #[instrument(name = "CoordinatorActor::stop_actors", skip(self))]
     fn stop_actors(&mut self, message: StopMessage) {
         Span::current().set_parent(message.context);
@@ -233,13 +341,21 @@ impl CoordinatorActor {
             ..message
         };
 
-        if let Some(addr) = &self.broadcaster_addr {
+        for (_, addr) in self.broadcaster_addrs.drain(..) {
+            if addr.connected() {
+                // it's fine not to check the result here
+                // because the actor in question will stop itself
+                addr.do_send(message.clone());
+            }
+        }
+
+        // the recorders complete their files when they stop
+        for addr in self.recorder_addrs.drain(..) {
             if addr.connected() {
                 // it's fine not to check the result here
                 // because the actor in question will stop itself
                 addr.do_send(message.clone());
             }
-            self.broadcaster_addr = None;
         }
 
         if let Some(addr) = &self.landing_detection_addr {
@@ -251,13 +367,13 @@ impl CoordinatorActor {
             self.landing_detection_addr = None;
         }
 
-        if let Some(addr) = &self.simconnect_addr {
+        if let Some(addr) = &self.source_addr {
             if addr.connected() {
                 // it's fine not to check the result here
                 // because the actor in question will stop itself
                 addr.do_send(message);
             }
-            self.simconnect_addr = None;
+            self.source_addr = None;
         }
     }
 }
@@ -1,11 +1,10 @@
 use actix::{Actor, ActorContext, Context, Handler};
 use opentelemetry_api::Context as OpenTelemetryContext;
-use simconnect_sdk::Airport;
 use tracing::{info, instrument, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::system::messages::{SimConnectDataMessage, StopMessage};
-use crate::system::simconnect_objects::{GpsData, OnGround};
+use crate::system::simconnect_objects::{Airport, GpsData, OnGround};
 
 #[derive(Debug, Default)]
 pub struct LandingDetectionActor {
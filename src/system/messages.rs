@@ -1,9 +1,14 @@
+use std::time::Duration;
+
 use actix::Message;
 use opentelemetry_api::Context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync;
 
-use crate::{broadcaster::BroadcasterConfig, cmd::ChannelResponse};
+use crate::{
+    broadcaster::BroadcasterConfig, cmd::ChannelResponse, input::SourceConfig,
+    recorder::RecorderConfig,
+};
 
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "()")]
@@ -22,12 +27,30 @@ pub enum CoordinatorMessage {
     Start {
         context: Context,
         refresh_rate: RefreshRate,
-        config: BroadcasterConfig,
+        source: SourceConfig,
+        configs: Vec<BroadcasterConfig>,
+        recorders: Vec<RecorderConfig>,
     },
     Stop {
         context: Context,
     },
     Status(GetStatusMessage),
+    BroadcastersStatus {
+        context: Context,
+        response_channel: sync::oneshot::Sender<ChannelResponse<Vec<BroadcasterStatus>>>,
+    },
+    PauseReplay {
+        context: Context,
+        paused: bool,
+    },
+    SeekReplay {
+        context: Context,
+        position: Duration,
+    },
+    SetReplaySpeed {
+        context: Context,
+        speed: f64,
+    },
 }
 
 #[derive(Debug, Message)]
@@ -45,11 +68,15 @@ pub struct GetStatusResponseMessage {
     pub response_channel: sync::oneshot::Sender<ChannelResponse<bool>>,
 }
 
-#[derive(Debug, Message)]
-#[rtype(result = "()")]
-pub struct SimConnectPing;
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcasterStatus {
+    #[serde(rename = "type")]
+    pub broadcaster_type: &'static str,
+    pub running: bool,
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RefreshRate {
     Slow,
@@ -62,3 +89,17 @@ pub struct StopMessage {
     pub reason: String,
     pub context: Context,
 }
+
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub struct ReplayControlMessage {
+    pub context: Context,
+    pub control: ReplayControl,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReplayControl {
+    Pause(bool),
+    Seek(Duration),
+    Speed(f64),
+}
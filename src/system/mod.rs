@@ -2,5 +2,6 @@ pub mod broadcaster_actor;
 pub mod coordinator_actor;
 pub mod landing_detection_actor;
 pub mod messages;
-pub mod simconnect_actor;
+pub mod recorder_actor;
 pub mod simconnect_objects;
+pub mod source_actor;
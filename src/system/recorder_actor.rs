@@ -0,0 +1,260 @@
+use actix::{Actor, ActorContext, AsyncContext, Context, Handler};
+use opentelemetry_api::Context as OpenTelemetryContext;
+use tracing::{debug, error, info, instrument, warn, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::recorder::{Gpx, Igc, RecorderConfig, RecorderExt, TelemetryRecorder};
+use crate::system::messages::{RefreshRate, SimConnectDataMessage, StopMessage};
+use crate::system::simconnect_objects::{Airport, AttitudeData, GpsData, OnGround, SimTime};
+
+/// Records the session next to the broadcasters, so a failing recording never stops the broadcasts.
+#[derive(Debug)]
+pub struct RecorderActor {
+    context: OpenTelemetryContext,
+    config: RecorderConfig,
+    refresh_rate: RefreshRate,
+    recorder: Option<Box<dyn RecorderExt>>,
+}
+
+impl RecorderActor {
+    pub fn new(
+        context: OpenTelemetryContext,
+        config: RecorderConfig,
+        refresh_rate: RefreshRate,
+    ) -> Self {
+        Self {
+            context,
+            config,
+            refresh_rate,
+            recorder: None,
+        }
+    }
+}
+
+impl Actor for RecorderActor {
+    type Context = Context<Self>;
+
+    #[instrument(name = "RecorderActor::started", skip(self, ctx))]
+    fn started(&mut self, ctx: &mut Self::Context) {
+        Span::current().set_parent(self.context.clone());
+
+        let addr = ctx.address();
+
+        let recorder = match self.config.clone() {
+            RecorderConfig::Gpx(config) => Gpx::new(config),
+            RecorderConfig::Igc(config) => Igc::new(config),
+            RecorderConfig::Telemetry(config) => TelemetryRecorder::new(config, self.refresh_rate),
+        };
+
+        match recorder {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                info!("RecorderActor started");
+            }
+            Err(_) => {
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to configure recorder".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        }
+    }
+
+    #[instrument(name = "RecorderActor::stopped", skip(self))]
+    fn stopped(&mut self, _: &mut Self::Context) {
+        Span::current().set_parent(self.context.clone());
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.finish() {
+                error!(error = ?e, "failed to finish the recording");
+            }
+        }
+
+        info!("RecorderActor stopped");
+    }
+}
+
+impl Handler<SimConnectDataMessage<GpsData>> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<SimConnectDataMessage<GpsData>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<GpsData>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            debug!("Recording SimConnectDataMessage<GpsData> message");
+
+            if let Err(e) = recorder.record(data) {
+                error!(error = ?e, "failed to record");
+
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to record".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current recorder");
+        }
+    }
+}
+
+impl Handler<SimConnectDataMessage<AttitudeData>> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<SimConnectDataMessage<AttitudeData>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<AttitudeData>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            debug!("Recording SimConnectDataMessage<AttitudeData> message");
+
+            if let Err(e) = recorder.record_attitude(data) {
+                error!(error = ?e, "failed to record");
+
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to record".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current recorder");
+        }
+    }
+}
+
+impl Handler<SimConnectDataMessage<OnGround>> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<SimConnectDataMessage<OnGround>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<OnGround>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            debug!("Recording SimConnectDataMessage<OnGround> message");
+
+            if let Err(e) = recorder.record_on_ground(data) {
+                error!(error = ?e, "failed to record");
+
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to record".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current recorder");
+        }
+    }
+}
+
+impl Handler<SimConnectDataMessage<Vec<Airport>>> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<SimConnectDataMessage<Vec<Airport>>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<Vec<Airport>>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            debug!("Recording SimConnectDataMessage<Vec<Airport>> message");
+
+            if let Err(e) = recorder.record_airports(data) {
+                error!(error = ?e, "failed to record");
+
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to record".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current recorder");
+        }
+    }
+}
+
+impl Handler<SimConnectDataMessage<SimTime>> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<SimConnectDataMessage<SimTime>>",
+        skip(self, message, ctx)
+    )]
+    fn handle(
+        &mut self,
+        message: SimConnectDataMessage<SimTime>,
+        ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        Span::current().set_parent(message.context);
+        let data = message.data;
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            debug!("Recording SimConnectDataMessage<SimTime> message");
+
+            if let Err(e) = recorder.record_sim_time(data) {
+                error!(error = ?e, "failed to record");
+
+                let addr = ctx.address();
+                addr.try_send(StopMessage {
+                    context: Span::current().context(),
+                    reason: "failed to record".to_string(),
+                })
+                .expect("RecorderActor queue is full");
+            }
+        } else {
+            warn!("failed to get the current recorder");
+        }
+    }
+}
+
+impl Handler<StopMessage> for RecorderActor {
+    type Result = ();
+
+    #[instrument(
+        name = "RecorderActor::handle::<StopMessage>",
+        skip(self, message, ctx)
+    )]
+    fn handle(&mut self, message: StopMessage, ctx: &mut Context<Self>) -> Self::Result {
+        Span::current().set_parent(message.context.clone());
+        info!(reason = ?message.reason, "RecorderActor stopping");
+        ctx.stop();
+    }
+}
@@ -1,25 +1,42 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
 use simconnect_sdk::SimConnectObject;
 
-#[derive(Debug, Clone, SimConnectObject)]
-#[simconnect(period = "second")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(windows, derive(SimConnectObject))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(windows, simconnect(period = "second"))]
 pub struct GpsData {
-    #[simconnect(name = "PLANE LATITUDE", unit = "Degrees")]
+    #[cfg_attr(windows, simconnect(name = "PLANE LATITUDE", unit = "Degrees"))]
     pub lat: f64,
-    #[simconnect(name = "PLANE LONGITUDE", unit = "Degrees")]
+    #[cfg_attr(windows, simconnect(name = "PLANE LONGITUDE", unit = "Degrees"))]
     pub lon: f64,
-    #[simconnect(name = "PLANE ALTITUDE", unit = "Meters")]
+    #[cfg_attr(windows, simconnect(name = "PLANE ALTITUDE", unit = "Meters"))]
     pub alt: f64,
-    #[simconnect(name = "PRESSURE ALTITUDE", unit = "Meters")]
+    #[cfg_attr(windows, simconnect(name = "PRESSURE ALTITUDE", unit = "Meters"))]
     pub pressure_altitude: f64,
-    #[simconnect(name = "GPS GROUND TRUE TRACK", unit = "Degrees")]
+    #[cfg_attr(windows, simconnect(name = "GPS GROUND TRUE TRACK", unit = "Degrees"))]
     pub gps_ground_true_track: f64,
-    #[simconnect(name = "GPS MAGVAR", unit = "Degrees")]
+    #[cfg_attr(windows, simconnect(name = "GPS MAGVAR", unit = "Degrees"))]
     pub gps_magnetic_variation: f64,
-    #[simconnect(name = "GPS GROUND SPEED", unit = "Meters per second")]
+    #[cfg_attr(
+        windows,
+        simconnect(name = "GPS GROUND SPEED", unit = "Meters per second")
+    )]
     pub gps_ground_speed: f64,
+    #[cfg_attr(
+        windows,
+        simconnect(name = "VERTICAL SPEED", unit = "Meters per second")
+    )]
+    pub vertical_speed: f64,
 }
 
 impl GpsData {
+    /// The simulator position is exact, so the outputs that report an accuracy use the one of
+    /// the GDL 90 ownship report (NACp 11, less than 3 m).
+    pub const POSITION_ACCURACY_M: f64 = 3.0;
+
     pub fn lat_as_degrees_minutes_dir(&self) -> (f64, f64, &str) {
         let abs = self.lat.abs();
         let deg = abs.trunc();
@@ -55,9 +72,74 @@ impl GpsData {
     }
 }
 
-#[derive(Debug, Clone, SimConnectObject)]
-#[simconnect(period = "visual-frame", condition = "changed")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(windows, derive(SimConnectObject))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(windows, simconnect(period = "second"))]
+pub struct AttitudeData {
+    #[cfg_attr(
+        windows,
+        simconnect(name = "PLANE HEADING DEGREES TRUE", unit = "Degrees")
+    )]
+    pub true_heading: f64,
+    #[cfg_attr(windows, simconnect(name = "PLANE PITCH DEGREES", unit = "Degrees"))]
+    pub pitch: f64,
+    #[cfg_attr(windows, simconnect(name = "PLANE BANK DEGREES", unit = "Degrees"))]
+    pub bank: f64,
+}
+
+impl AttitudeData {
+    /// SimConnect reports a nose-up attitude as a negative pitch.
+    pub fn pitch_up(&self) -> f64 {
+        -self.pitch
+    }
+
+    /// SimConnect reports a right bank as a negative bank angle.
+    pub fn roll_right(&self) -> f64 {
+        -self.bank
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(windows, derive(SimConnectObject))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(windows, simconnect(period = "visual-frame", condition = "changed"))]
 pub struct OnGround {
-    #[simconnect(name = "SIM ON GROUND")]
+    #[cfg_attr(windows, simconnect(name = "SIM ON GROUND"))]
     pub sim_on_ground: bool,
 }
+
+#[derive(Debug, Clone)]
+#[cfg_attr(windows, derive(SimConnectObject))]
+#[cfg_attr(windows, simconnect(period = "second"))]
+pub struct SimTime {
+    #[cfg_attr(windows, simconnect(name = "ABSOLUTE TIME", unit = "Seconds"))]
+    pub absolute_time: f64,
+}
+
+impl SimTime {
+    // the absolute time counts the seconds since 0001-01-01
+    const UNIX_EPOCH_ABSOLUTE_TIME: f64 = 62_135_596_800.0;
+
+    pub fn from_date_time(date: &DateTime<Utc>) -> Self {
+        Self {
+            absolute_time: date.timestamp_millis() as f64 / 1000.0 + Self::UNIX_EPOCH_ABSOLUTE_TIME,
+        }
+    }
+
+    pub fn as_date_time(&self) -> Option<DateTime<Utc>> {
+        let seconds = self.absolute_time - Self::UNIX_EPOCH_ABSOLUTE_TIME;
+
+        Utc.timestamp_millis_opt((seconds * 1000.0).round() as i64)
+            .single()
+    }
+}
+
+// a copy of the SimConnect airport, whose type is only available on Windows
+#[derive(Debug, Clone)]
+pub struct Airport {
+    pub icao: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub alt: f64,
+}
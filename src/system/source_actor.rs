@@ -0,0 +1,308 @@
+use std::time::SystemTime;
+
+use actix::{Actor, ActorContext, Addr, AsyncContext, Context, Handler};
+use opentelemetry_api::Context as OpenTelemetryContext;
+use tracing::{debug_span, error, info, instrument, warn, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::input::{DataSource, SourceConfig, SourceEvent, SourceOptions};
+use crate::system::broadcaster_actor::BroadcasterActor;
+use crate::system::coordinator_actor::CoordinatorActor;
+use crate::system::landing_detection_actor::LandingDetectionActor;
+use crate::system::messages::{
+    GetStatusMessage, GetStatusResponseMessage, ReplayControlMessage, SimConnectDataMessage,
+    StopMessage,
+};
+use crate::system::recorder_actor::RecorderActor;
+
+const SOURCE_TIMEOUT_S: u64 = 15;
+
+/// Polls the data source of the session, whichever it is, and sends its data
+/// to the broadcasters, the recorders and the landing detection.
+#[derive(Debug)]
+pub struct SourceActor {
+    context: OpenTelemetryContext,
+    config: SourceConfig,
+    options: SourceOptions,
+    coordinator_addr: Addr<CoordinatorActor>,
+    broadcaster_addrs: Vec<Addr<BroadcasterActor>>,
+    recorder_addrs: Vec<Addr<RecorderActor>>,
+    landing_detection_addr: Addr<LandingDetectionActor>,
+    source: Option<Box<dyn DataSource>>,
+    last_ping: Option<SystemTime>,
+}
+
+impl SourceActor {
+    pub fn new(
+        context: OpenTelemetryContext,
+        config: SourceConfig,
+        options: SourceOptions,
+        coordinator_addr: Addr<CoordinatorActor>,
+        broadcaster_addrs: Vec<Addr<BroadcasterActor>>,
+        recorder_addrs: Vec<Addr<RecorderActor>>,
+        landing_detection_addr: Addr<LandingDetectionActor>,
+    ) -> Self {
+        Self {
+            context,
+            config,
+            options,
+            coordinator_addr,
+            broadcaster_addrs,
+            recorder_addrs,
+            landing_detection_addr,
+            source: None,
+            last_ping: None,
+        }
+    }
+
+    fn poll(&mut self, ctx: &mut Context<Self>) {
+        let Some(source) = &mut self.source else {
+            return;
+        };
+
+        let events = match source.poll() {
+            Ok(events) => events,
+            Err(e) => {
+                error!(
+                    error = ?e,
+                    source = self.config.source_type(),
+                    "failed to poll the data source"
+                );
+
+                self.source = None;
+                ctx.address()
+                    .try_send(StopMessage {
+                        context: self.context.clone(),
+                        reason: "failed to poll the data source".to_string(),
+                    })
+                    .expect("SourceActor queue is full");
+
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            return;
+        }
+
+        let span = debug_span!("SourceActor::poll");
+        span.set_parent(self.context.clone());
+        let _ = span.enter();
+
+        for event in events {
+            match event {
+                SourceEvent::Gps(data) => {
+                    let message = SimConnectDataMessage {
+                        context: span.context(),
+                        data,
+                    };
+
+                    if self.options.landing_detection_enabled {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        self.landing_detection_addr.do_send(message.clone());
+                    }
+
+                    for broadcaster_addr in &self.broadcaster_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        broadcaster_addr.do_send(message.clone());
+                    }
+
+                    for recorder_addr in &self.recorder_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        recorder_addr.do_send(message.clone());
+                    }
+                }
+                SourceEvent::Attitude(data) => {
+                    let message = SimConnectDataMessage {
+                        context: span.context(),
+                        data,
+                    };
+
+                    for broadcaster_addr in &self.broadcaster_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        broadcaster_addr.do_send(message.clone());
+                    }
+
+                    for recorder_addr in &self.recorder_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        recorder_addr.do_send(message.clone());
+                    }
+                }
+                #[cfg(windows)]
+                SourceEvent::OnGround(data) => {
+                    let message = SimConnectDataMessage {
+                        context: span.context(),
+                        data,
+                    };
+
+                    if self.options.landing_detection_enabled {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        self.landing_detection_addr.do_send(message.clone());
+                    }
+
+                    for recorder_addr in &self.recorder_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        recorder_addr.do_send(message.clone());
+                    }
+                }
+                #[cfg(windows)]
+                SourceEvent::Airports(data) => {
+                    let message = SimConnectDataMessage {
+                        context: span.context(),
+                        data,
+                    };
+
+                    if self.options.landing_detection_enabled {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        self.landing_detection_addr.do_send(message.clone());
+                    }
+
+                    for recorder_addr in &self.recorder_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        recorder_addr.do_send(message.clone());
+                    }
+                }
+                SourceEvent::SimTime(data) => {
+                    let message = SimConnectDataMessage {
+                        context: span.context(),
+                        data,
+                    };
+
+                    for recorder_addr in &self.recorder_addrs {
+                        // it's fine not to check the result here
+                        // because the actor will be stopped anyway
+                        recorder_addr.do_send(message.clone());
+                    }
+                }
+                SourceEvent::Ping => {
+                    self.last_ping.replace(SystemTime::now());
+                }
+                SourceEvent::Quit(reason) => {
+                    self.source = None;
+
+                    ctx.address()
+                        .try_send(StopMessage {
+                            context: span.context(),
+                            reason,
+                        })
+                        .expect("SourceActor queue is full");
+
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Actor for SourceActor {
+    type Context = Context<Self>;
+
+    #[instrument(
+        name = "SourceActor::started",
+        skip(self, ctx),
+        fields(source = self.config.source_type())
+    )]
+    fn started(&mut self, ctx: &mut Self::Context) {
+        Span::current().set_parent(self.context.clone());
+
+        match self.config.open(self.options) {
+            Ok(source) => {
+                ctx.run_interval(source.poll_interval(), |actor, ctx| actor.poll(ctx));
+                self.source = Some(source);
+
+                info!("SourceActor started");
+            }
+            Err(e) => {
+                error!(error = ?e, "failed to open the data source");
+
+                ctx.address()
+                    .try_send(StopMessage {
+                        context: Span::current().context(),
+                        reason: "failed to open the data source".to_string(),
+                    })
+                    .expect("SourceActor queue is full");
+            }
+        }
+    }
+
+    #[instrument(name = "SourceActor::stopped", skip(self))]
+    fn stopped(&mut self, _: &mut Self::Context) {
+        Span::current().set_parent(self.context.clone());
+
+        // e.g. the NMEA input stops reading when its source is dropped
+        self.source = None;
+
+        info!("SourceActor stopped");
+    }
+}
+
+impl Handler<ReplayControlMessage> for SourceActor {
+    type Result = ();
+
+    #[instrument(
+        name = "SourceActor::handle::<ReplayControlMessage>",
+        skip(self, message)
+    )]
+    fn handle(&mut self, message: ReplayControlMessage, _: &mut Context<Self>) -> Self::Result {
+        Span::current().set_parent(message.context);
+
+        if let Some(source) = &mut self.source {
+            source.control(message.control);
+        }
+    }
+}
+
+impl Handler<GetStatusMessage> for SourceActor {
+    type Result = ();
+
+    #[instrument(name = "SourceActor::handle::<GetStatusMessage>", skip(self, message))]
+    fn handle(&mut self, message: GetStatusMessage, _: &mut Context<Self>) -> Self::Result {
+        Span::current().set_parent(message.context.clone());
+
+        let status = match self.last_ping {
+            Some(last_ping) => {
+                let now = SystemTime::now();
+                let elapsed = now.duration_since(last_ping).unwrap_or_default();
+                let status = elapsed.as_secs() < SOURCE_TIMEOUT_S;
+
+                if !status {
+                    warn!(
+                        source = self.config.source_type(),
+                        "Last ping is older than {SOURCE_TIMEOUT_S} seconds."
+                    );
+                }
+
+                status
+            }
+            None => false,
+        };
+
+        // it's fine not to check the result here
+        // because the worst that can happen is that the get status command will timeout
+        self.coordinator_addr.do_send(GetStatusResponseMessage {
+            context: Span::current().context(),
+            status,
+            response_channel: message.response_channel,
+        });
+    }
+}
+
+impl Handler<StopMessage> for SourceActor {
+    type Result = ();
+
+    #[instrument(name = "SourceActor::handle::<StopMessage>", skip(self, message, ctx))]
+    fn handle(&mut self, message: StopMessage, ctx: &mut Context<Self>) -> Self::Result {
+        Span::current().set_parent(message.context.clone());
+        info!(reason = ?message.reason, "SourceActor stopping");
+        ctx.stop();
+    }
+}